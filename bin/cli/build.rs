@@ -1,3 +1,4 @@
 fn main() {
     sp1_build::build_program("../../crates/zk");
+    sp1_build::build_program("../../crates/zk-aggregate");
 }
@@ -0,0 +1,115 @@
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use sp1_sdk::{HashableKey, SP1ProofWithPublicValues, SP1VerifyingKey};
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+use crate::proof_system::{ProofSystem, SP1Prover};
+
+const PROOF_FILE: &str = "proof.bin";
+const VKEY_FILE: &str = "vkey.json";
+const METADATA_FILE: &str = "metadata.json";
+
+/// SP1 program that generated a proof.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Program {
+    /// Execution of a single ONNX operator (`zkopml-zk`)
+    Operator,
+    /// Aggregation of operator proofs (`zkopml-zk-aggregate`)
+    Aggregate,
+}
+
+/// Metadata of a saved proof.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ProofMetadata {
+    pub program: Program,
+    /// Verification key of the program (bytes32 hex), as set in the contracts.
+    pub vkey: String,
+    pub sp1_prover: SP1Prover,
+    pub proof_system: ProofSystem,
+    /// Unix timestamp of the proof generation.
+    pub created_at: u64,
+}
+
+/// Proof saved to disk, so it can be verified offline or submitted on-chain without re-proving.
+///
+/// An artifact is a directory with the proof and its public values (`proof.bin`), the
+/// verification key of the program (`vkey.json`) and the metadata (`metadata.json`).
+pub struct ProofArtifact {
+    pub proof: SP1ProofWithPublicValues,
+    pub vk: SP1VerifyingKey,
+    pub metadata: ProofMetadata,
+}
+
+impl ProofArtifact {
+    pub fn new(
+        proof: SP1ProofWithPublicValues,
+        vk: SP1VerifyingKey,
+        program: Program,
+        sp1_prover: SP1Prover,
+        proof_system: ProofSystem,
+    ) -> anyhow::Result<Self> {
+        let metadata = ProofMetadata {
+            program,
+            vkey: vk.bytes32(),
+            sp1_prover,
+            proof_system,
+            created_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)?
+                .as_secs(),
+        };
+        Ok(Self {
+            proof,
+            vk,
+            metadata,
+        })
+    }
+
+    /// Saves the artifact to `dir`, creating the directory if needed.
+    pub fn save(&self, dir: impl AsRef<Path>) -> anyhow::Result<()> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+        self.proof
+            .save(dir.join(PROOF_FILE))
+            .with_context(|| format!("Failed to save the proof to {}", dir.display()))?;
+        std::fs::write(dir.join(VKEY_FILE), serde_json::to_vec(&self.vk)?)?;
+        std::fs::write(
+            dir.join(METADATA_FILE),
+            serde_json::to_vec_pretty(&self.metadata)?,
+        )?;
+        info!("Saved proof artifact to {}", dir.display());
+
+        Ok(())
+    }
+
+    /// Loads the artifact saved in `dir`.
+    pub fn load(dir: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let dir = dir.as_ref();
+        let proof = SP1ProofWithPublicValues::load(dir.join(PROOF_FILE))
+            .with_context(|| format!("Failed to load the proof from {}", dir.display()))?;
+        let vk = serde_json::from_slice(&std::fs::read(dir.join(VKEY_FILE))?)?;
+        let metadata = serde_json::from_slice(&std::fs::read(dir.join(METADATA_FILE))?)?;
+
+        Ok(Self {
+            proof,
+            vk,
+            metadata,
+        })
+    }
+}
+
+/// Directory of the proof of a single operator.
+pub fn operator_dir(output_dir: &str, operator_index: usize) -> PathBuf {
+    Path::new(output_dir).join(format!("operator-{}", operator_index))
+}
+
+/// Directory of the aggregated proof of the operators `first_operator..=last_operator`.
+pub fn aggregate_dir(output_dir: &str, first_operator: usize, last_operator: usize) -> PathBuf {
+    Path::new(output_dir).join(format!("aggregate-{}-{}", first_operator, last_operator))
+}
+
+/// Directory of the proof resolving a challenge.
+pub fn challenge_dir(output_dir: &str, challenge_id: impl std::fmt::Display) -> PathBuf {
+    Path::new(output_dir).join(format!("challenge-{}", challenge_id))
+}
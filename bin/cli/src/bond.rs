@@ -0,0 +1,188 @@
+use alloy::{
+    primitives::{Address, U256, utils::format_ether},
+    providers::{Provider, ProviderBuilder},
+};
+use tracing::info;
+use zkopml_contracts::ZkopmlClient;
+
+use crate::{
+    manifest::{DeploymentManifest, contract_address},
+    provider, signer,
+};
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct BondArgs {
+    #[arg(long, short, help = "Verbosity level (0-4)", action = clap::ArgAction::Count)]
+    pub v: u8,
+
+    /// Address of the Ethereum node endpoint to use
+    #[clap(long)]
+    pub eth_node_address: String,
+
+    /// Path of the deployment manifest written by `deploy`, instead of the contract addresses
+    #[clap(long)]
+    pub deployment: Option<String>,
+
+    /// Address of the ModelRegistry contract
+    #[clap(long, required_unless_present = "deployment")]
+    pub model_registry_address: Option<Address>,
+
+    /// Address of the FaultProof contract
+    #[clap(long, required_unless_present = "deployment")]
+    pub fault_proof_address: Option<Address>,
+
+    /// Signer of the bond owner: hex private key, keystore:<path>, mnemonic:<path>
+    /// or remote:<url>
+    #[clap(long)]
+    pub user_key: String,
+
+    #[command(subcommand)]
+    pub action: BondAction,
+}
+
+#[derive(clap::Subcommand, Debug, Clone)]
+pub enum BondAction {
+    /// Deposit a bond, locked by inference responses and challenges
+    Deposit {
+        /// Amount to deposit (in wei)
+        #[clap(long)]
+        amount: U256,
+    },
+    /// Withdraw the free bond
+    Withdraw {
+        /// Amount to withdraw (in wei), defaults to the whole free bond
+        #[clap(long)]
+        amount: Option<U256>,
+    },
+    /// Show the free bond of an account and the bonds required by the contracts
+    Show {
+        /// Account to show the bond of (defaults to the user address)
+        #[clap(long)]
+        account: Option<Address>,
+
+        /// Inference to show the locked response bond and the escrowed fee of
+        #[clap(long)]
+        inference_id: Option<U256>,
+    },
+    /// Release the response bond and the fee of an inference once its challenge window expired
+    /// and its challenges are resolved (see also `claim`)
+    Release {
+        /// Inference to release the response bond and the fee of
+        #[clap(long)]
+        inference_id: U256,
+    },
+}
+
+pub async fn bond(args: BondArgs) -> anyhow::Result<()> {
+    // Initialize the user wallet
+    let user_wallet = signer::wallet(&args.user_key).await?;
+    let user_provider = ProviderBuilder::new()
+        .wallet(&user_wallet)
+        .connect_client(provider::rpc_client(&args.eth_node_address).await?);
+    let user_address = user_wallet.default_signer().address();
+    info!("User address: {}", user_address);
+
+    // Read the contract addresses from the deployment manifest, if any
+    let deployment = DeploymentManifest::load_optional(args.deployment.as_ref())?;
+    if let Some(deployment) = deployment.as_ref() {
+        deployment.check_chain_id(&user_provider).await?;
+    }
+    let model_registry_address = contract_address(
+        args.model_registry_address,
+        deployment.as_ref(),
+        |deployment| deployment.model_registry,
+        "model-registry-address",
+    )?;
+    let fault_proof_address = contract_address(
+        args.fault_proof_address,
+        deployment.as_ref(),
+        |deployment| deployment.fault_proof,
+        "fault-proof-address",
+    )?;
+
+    let client = ZkopmlClient::new(user_provider.clone(), model_registry_address)
+        .with_fault_proof(fault_proof_address);
+
+    match args.action {
+        BondAction::Deposit { amount } => {
+            info!("Depositing a bond of {} ETH", format_ether(amount));
+            client.deposit_bond(amount).await?;
+            show_bond(&client, user_address).await?;
+        }
+        BondAction::Withdraw { amount } => {
+            let amount = match amount {
+                Some(amount) => amount,
+                None => client.bond(user_address).await?,
+            };
+            info!("Withdrawing a bond of {} ETH", format_ether(amount));
+            client.withdraw_bond(amount).await?;
+            show_bond(&client, user_address).await?;
+        }
+        BondAction::Show {
+            account,
+            inference_id,
+        } => {
+            show_bond(&client, account.unwrap_or(user_address)).await?;
+            info!(
+                "Response bond: {} ETH, challenge bond: {} ETH",
+                format_ether(client.response_bond().await?),
+                format_ether(client.challenge_bond().await?)
+            );
+            if let Some(inference_id) = inference_id {
+                info!(
+                    "Response bond locked by inference {}: {} ETH",
+                    inference_id,
+                    format_ether(client.locked_response_bond(inference_id).await?)
+                );
+                info!(
+                    "Fee escrowed by inference {}: {} ETH",
+                    inference_id,
+                    format_ether(client.fee(inference_id).await?)
+                );
+            }
+        }
+        BondAction::Release { inference_id } => {
+            info!(
+                "Releasing the response bond and the fee of inference {}",
+                inference_id
+            );
+            client.release_response_bond(inference_id).await?;
+            show_bond(&client, user_address).await?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn show_bond<P: Provider + Clone>(
+    client: &ZkopmlClient<P>,
+    account: Address,
+) -> anyhow::Result<()> {
+    info!(
+        "Free bond of {}: {} ETH",
+        account,
+        format_ether(client.bond(account).await?)
+    );
+
+    Ok(())
+}
+
+/// Fails if the free bond of the account does not cover the bond locked by an action.
+pub async fn ensure_bond<P: Provider + Clone>(
+    client: &ZkopmlClient<P>,
+    account: Address,
+    required: U256,
+    action: &str,
+) -> anyhow::Result<()> {
+    let bond = client.bond(account).await?;
+    anyhow::ensure!(
+        bond >= required,
+        "Insufficient bond for {}: {} has {} ETH, {} ETH is required (deposit with `bond deposit`)",
+        action,
+        account,
+        format_ether(bond),
+        format_ether(required)
+    );
+
+    Ok(())
+}
@@ -0,0 +1,182 @@
+use alloy::{
+    eips::BlockNumberOrTag,
+    primitives::{Address, U256, utils::format_ether},
+    providers::{Provider, ProviderBuilder},
+};
+use anyhow::Context;
+use tracing::info;
+use zkopml_contracts::{
+    ModelRegistry::ModelRegistryEvents, ZkopmlClient, ZkopmlEvent, fetch_events,
+};
+
+use crate::{
+    manifest::{DeploymentManifest, contract_address},
+    provider, signer,
+};
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct ClaimArgs {
+    #[arg(long, short, help = "Verbosity level (0-4)", action = clap::ArgAction::Count)]
+    pub v: u8,
+
+    /// Address of the Ethereum node endpoint to use
+    #[clap(long)]
+    pub eth_node_address: String,
+
+    /// Path of the deployment manifest written by `deploy`, instead of the contract addresses
+    #[clap(long)]
+    pub deployment: Option<String>,
+
+    /// Address of the ModelRegistry contract
+    #[clap(long, required_unless_present = "deployment")]
+    pub model_registry_address: Option<Address>,
+
+    /// Address of the FaultProof contract
+    #[clap(long, required_unless_present = "deployment")]
+    pub fault_proof_address: Option<Address>,
+
+    /// Signer of the submitter: hex private key, keystore:<path>, mnemonic:<path>
+    /// or remote:<url>
+    #[clap(long)]
+    pub user_key: String,
+
+    /// Inferences to claim (can be repeated), defaults to all the inferences responded by the
+    /// submitter
+    #[clap(long)]
+    pub inference_id: Vec<U256>,
+
+    /// Block to search the responses of the submitter from (defaults to the deploy block of the
+    /// deployment, otherwise to the genesis block)
+    #[clap(long)]
+    pub from_block: Option<u64>,
+
+    /// Withdraw the whole free bond (including the claimed fees) afterwards
+    #[clap(long)]
+    pub withdraw: bool,
+}
+
+/// Claims the fees and the response bonds of the optimistic inferences responded by the submitter,
+/// once their challenge window expired and their challenges are resolved.
+pub async fn claim(args: ClaimArgs) -> anyhow::Result<()> {
+    // Initialize the user wallet
+    let user_wallet = signer::wallet(&args.user_key).await?;
+    let user_provider = ProviderBuilder::new()
+        .wallet(&user_wallet)
+        .connect_client(provider::rpc_client(&args.eth_node_address).await?);
+    let user_address = user_wallet.default_signer().address();
+    info!("User address: {}", user_address);
+
+    // Read the contract addresses from the deployment manifest, if any
+    let deployment = DeploymentManifest::load_optional(args.deployment.as_ref())?;
+    if let Some(deployment) = deployment.as_ref() {
+        deployment.check_chain_id(&user_provider).await?;
+    }
+    let model_registry_address = contract_address(
+        args.model_registry_address,
+        deployment.as_ref(),
+        |deployment| deployment.model_registry,
+        "model-registry-address",
+    )?;
+    let fault_proof_address = contract_address(
+        args.fault_proof_address,
+        deployment.as_ref(),
+        |deployment| deployment.fault_proof,
+        "fault-proof-address",
+    )?;
+
+    let client = ZkopmlClient::new(user_provider.clone(), model_registry_address)
+        .with_fault_proof(fault_proof_address);
+
+    // Find the inferences responded by the submitter
+    let inference_ids = if args.inference_id.is_empty() {
+        let deploy_block = deployment
+            .as_ref()
+            .map(|deployment| deployment.deploy_block);
+        let from_block = args.from_block.or(deploy_block).unwrap_or_default();
+        let to_block = user_provider.get_block_number().await?;
+        fetch_events(
+            &user_provider,
+            &[model_registry_address],
+            from_block,
+            to_block,
+        )
+        .await?
+        .into_iter()
+        .filter_map(|event| match event.event {
+            ZkopmlEvent::ModelRegistry(ModelRegistryEvents::InferenceResponded(response))
+                if response.responder == user_address =>
+            {
+                Some(response.inferenceId)
+            }
+            _ => None,
+        })
+        .collect()
+    } else {
+        args.inference_id.clone()
+    };
+    info!("Found {} inferences to claim", inference_ids.len());
+
+    let challenge_window = client.fault_proof()?.challengeWindow().call().await?;
+    let latest_block = user_provider
+        .get_block_by_number(BlockNumberOrTag::Latest)
+        .await?
+        .context("Missing the latest block")?;
+    let now = U256::from(latest_block.header.timestamp);
+
+    let mut claimed = U256::ZERO;
+    for inference_id in inference_ids {
+        let inference = client
+            .model_registry()
+            .getInference(inference_id)
+            .call()
+            .await?;
+        if inference.zk || inference.responder != user_address {
+            // The fees of full-ZK inferences are paid on response
+            continue;
+        }
+        let amount =
+            client.locked_response_bond(inference_id).await? + client.fee(inference_id).await?;
+        if amount.is_zero() {
+            info!(
+                "Nothing to claim for inference {} (already claimed or slashed)",
+                inference_id
+            );
+            continue;
+        }
+        if inference.timestampResponse + challenge_window > now {
+            info!(
+                "The challenge window of inference {} has not expired yet",
+                inference_id
+            );
+            continue;
+        }
+        if !client.open_challenges(inference_id).await?.is_zero() {
+            info!("Inference {} has open challenges", inference_id);
+            continue;
+        }
+
+        info!(
+            "Claiming {} ETH for inference {}",
+            format_ether(amount),
+            inference_id
+        );
+        client.release_response_bond(inference_id).await?;
+        claimed += amount;
+    }
+    info!("Claimed {} ETH", format_ether(claimed));
+
+    if args.withdraw {
+        let amount = client.bond(user_address).await?;
+        if !amount.is_zero() {
+            info!("Withdrawing a bond of {} ETH", format_ether(amount));
+            client.withdraw_bond(amount).await?;
+        }
+    }
+    info!(
+        "Free bond of {}: {} ETH",
+        user_address,
+        format_ether(client.bond(user_address).await?)
+    );
+
+    Ok(())
+}
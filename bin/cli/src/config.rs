@@ -0,0 +1,253 @@
+use clap::{Arg, ArgAction, Command, CommandFactory, FromArgMatches};
+use std::{collections::BTreeMap, ffi::OsString, path::Path};
+
+/// Configuration file read when `--config` is not given.
+pub const DEFAULT_CONFIG_PATH: &str = "zkopml.toml";
+
+/// Profile used when `--profile` is not given (if the configuration file has it).
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Prefix of the environment variables of the options, e.g. `ZKOPML_ETH_NODE_ADDRESS` for
+/// `--eth-node-address`.
+const ENV_PREFIX: &str = "ZKOPML_";
+
+/// `required_unless_present` requirements of the commands (option, option satisfying it). A
+/// profile value is only a default, which does not satisfy the requirement for clap: it is cleared
+/// when the profile gives the satisfying option, and the commands check the option themselves.
+const PROFILE_REQUIREMENTS: [(&str, &str); 3] = [
+    ("model-registry-address", "deployment"),
+    ("fault-proof-address", "deployment"),
+    ("sp1-verifier-address", "mock-verifier"),
+];
+
+/// Options of a configuration profile, by long option name. Nested tables hold the options of a
+/// single command (and of its subcommands), overriding the ones of the profile.
+///
+/// ```toml
+/// [devnet]
+/// eth-node-address = "ws://127.0.0.1:8546"
+/// deployment = "deployment.json"
+///
+/// [devnet.submit]
+/// user-key = "0x..."
+/// ```
+#[derive(Debug, Clone, Default)]
+struct Options {
+    values: BTreeMap<String, String>,
+    commands: BTreeMap<String, Options>,
+}
+
+impl Options {
+    fn from_table(table: toml::Table, path: &str) -> anyhow::Result<Self> {
+        let mut options = Self::default();
+        for (key, value) in table {
+            let key = key.replace('_', "-");
+            let value = match value {
+                toml::Value::Table(table) => {
+                    let command = Self::from_table(table, &format!("{path}.{key}"))?;
+                    options.commands.insert(key, command);
+                    continue;
+                }
+                toml::Value::String(value) => value,
+                toml::Value::Integer(value) => value.to_string(),
+                toml::Value::Float(value) => value.to_string(),
+                toml::Value::Boolean(value) => value.to_string(),
+                value => anyhow::bail!(
+                    "Unsupported value of `{}` in `{}`: {}",
+                    key,
+                    path,
+                    value.type_str()
+                ),
+            };
+            options.values.insert(key, value);
+        }
+
+        Ok(options)
+    }
+
+    /// Checks that the options exist in the commands they apply to.
+    fn validate(&self, command: &Command, path: &str) -> anyhow::Result<()> {
+        let longs = long_options(command);
+        for key in self.values.keys() {
+            anyhow::ensure!(
+                longs.contains(key),
+                "Unknown option `{}` in `{}`",
+                key,
+                path
+            );
+        }
+        for (name, options) in &self.commands {
+            let subcommand = command
+                .find_subcommand(name)
+                .ok_or_else(|| anyhow::anyhow!("Unknown command `{}` in `{}`", name, path))?;
+            options.validate(subcommand, &format!("{path}.{name}"))?;
+        }
+
+        Ok(())
+    }
+
+    /// Sets the environment variables and the profile defaults of the options of the command and
+    /// of its subcommands.
+    fn apply(&self, mut command: Command, inherited: &BTreeMap<String, String>) -> Command {
+        let mut values = inherited.clone();
+        values.extend(self.values.clone());
+        command = command.mut_args(|arg| configure_arg(arg, &values));
+
+        let names: Vec<String> = command
+            .get_subcommands()
+            .map(|subcommand| subcommand.get_name().to_string())
+            .collect();
+        for name in names {
+            let options = self.commands.get(&name).cloned().unwrap_or_default();
+            command =
+                command.mut_subcommand(&name, |subcommand| options.apply(subcommand, &values));
+        }
+
+        command
+    }
+}
+
+/// Parses the command line, layering the options of the selected configuration profile and of
+/// the environment under the flags: flags override environment variables, which override the
+/// profile, which overrides the built-in defaults.
+pub fn parse<C: CommandFactory + FromArgMatches>() -> anyhow::Result<C> {
+    let args: Vec<OsString> = std::env::args_os().collect();
+    let (config_path, profile) = selection(&args);
+    let command = C::command()
+        .arg(
+            Arg::new("config")
+                .long("config")
+                .global(true)
+                .env(format!("{ENV_PREFIX}CONFIG"))
+                .help(format!(
+                    "Path of the configuration file (TOML) [default: {DEFAULT_CONFIG_PATH}]"
+                )),
+        )
+        .arg(
+            Arg::new("profile")
+                .long("profile")
+                .global(true)
+                .env(format!("{ENV_PREFIX}PROFILE"))
+                .help(format!(
+                    "Profile of the configuration file to use [default: {DEFAULT_PROFILE}]"
+                )),
+        );
+
+    let options = load_profile(config_path.as_deref(), profile.as_deref())?;
+    options.validate(&command, profile.as_deref().unwrap_or(DEFAULT_PROFILE))?;
+    let command = options.apply(command, &BTreeMap::new());
+
+    let matches = command.get_matches_from(args);
+    Ok(C::from_arg_matches(&matches).unwrap_or_else(|error| error.exit()))
+}
+
+/// Returns the configuration file and the profile selected by the flags or the environment. They
+/// are needed before parsing, to set the defaults of the other options.
+fn selection(args: &[OsString]) -> (Option<String>, Option<String>) {
+    let mut config_path = None;
+    let mut profile = None;
+    let mut args = args.iter().skip(1).filter_map(|arg| arg.to_str());
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            break;
+        }
+        if let Some(value) = arg.strip_prefix("--config=") {
+            config_path = Some(value.to_string());
+        } else if arg == "--config" {
+            config_path = args.next().map(str::to_string);
+        } else if let Some(value) = arg.strip_prefix("--profile=") {
+            profile = Some(value.to_string());
+        } else if arg == "--profile" {
+            profile = args.next().map(str::to_string);
+        }
+    }
+    let env = |name: &str| std::env::var(format!("{ENV_PREFIX}{name}")).ok();
+
+    (
+        config_path.or_else(|| env("CONFIG")),
+        profile.or_else(|| env("PROFILE")),
+    )
+}
+
+/// Loads a profile of the configuration file. The default file and profile are optional, the
+/// selected ones must exist.
+fn load_profile(config_path: Option<&str>, profile: Option<&str>) -> anyhow::Result<Options> {
+    let path = config_path.unwrap_or(DEFAULT_CONFIG_PATH);
+    if !Path::new(path).exists() {
+        anyhow::ensure!(
+            config_path.is_none() && profile.is_none(),
+            "Missing configuration file {}",
+            path
+        );
+        return Ok(Options::default());
+    }
+
+    let mut config: toml::Table = toml::from_str(&std::fs::read_to_string(path)?)
+        .map_err(|error| anyhow::anyhow!("Invalid configuration file {}: {}", path, error))?;
+    let name = profile.unwrap_or(DEFAULT_PROFILE);
+    let table = match config.remove(name) {
+        Some(toml::Value::Table(table)) => table,
+        Some(_) => anyhow::bail!("Profile `{}` of {} is not a table", name, path),
+        None => {
+            anyhow::ensure!(
+                profile.is_none(),
+                "Unknown profile `{}` in {} (available: {})",
+                name,
+                path,
+                config.keys().cloned().collect::<Vec<_>>().join(", ")
+            );
+            return Ok(Options::default());
+        }
+    };
+
+    Options::from_table(table, name)
+}
+
+fn configure_arg(arg: Arg, values: &BTreeMap<String, String>) -> Arg {
+    let Some(long) = arg.get_long().map(str::to_string) else {
+        return arg;
+    };
+    if matches!(long.as_str(), "config" | "profile") || matches!(arg.get_action(), ArgAction::Count)
+    {
+        return arg;
+    }
+
+    // The environment may hold signers (private keys), its values are never shown in the help
+    let mut arg = arg.env(env_var(&long)).hide_env_values(true);
+    let satisfied = PROFILE_REQUIREMENTS.iter().any(|(option, required)| {
+        *option == long && values.get(*required).is_some_and(|value| value != "false")
+    });
+    if satisfied {
+        arg = arg.required_unless_present(None::<&str>);
+    }
+    match values.get(&long) {
+        Some(value) => arg
+            .default_value(value.clone())
+            .hide_default_value(is_signer(&long))
+            .required(false),
+        None => arg,
+    }
+}
+
+/// Whether the option is a signer (e.g. `--user-key`), whose profile value is a secret.
+fn is_signer(long: &str) -> bool {
+    long.ends_with("-key")
+}
+
+/// Environment variable of an option.
+pub fn env_var(long: &str) -> String {
+    format!("{ENV_PREFIX}{}", long.replace('-', "_").to_uppercase())
+}
+
+/// Long option names of a command and of its subcommands.
+fn long_options(command: &Command) -> Vec<String> {
+    let mut longs: Vec<String> = command
+        .get_arguments()
+        .filter_map(|arg| arg.get_long().map(str::to_string))
+        .collect();
+    for subcommand in command.get_subcommands() {
+        longs.extend(long_options(subcommand));
+    }
+
+    longs
+}
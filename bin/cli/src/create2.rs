@@ -0,0 +1,81 @@
+use alloy::{
+    hex,
+    network::TransactionBuilder,
+    primitives::{Address, B256, Bytes, U256, address},
+    providers::Provider,
+    rpc::types::TransactionRequest,
+};
+use tracing::info;
+
+/// Deterministic deployment proxy (`contracts/create2`), at the same address on every chain.
+pub const CREATE2_PROXY: Address = address!("4e59b44847b379578588920cA78FbF26c0B4956C");
+
+/// One-time account signing the deployment transaction of the proxy.
+const CREATE2_PROXY_SIGNER: Address = address!("3fab184622dc19b6109349b94811493bf2a45362");
+
+/// Pre-signed (without chain id) deployment transaction of the proxy.
+const CREATE2_PROXY_DEPLOYMENT_TX: &str = "0xf8a58085174876e800830186a08080b853604580600e600039806000f350fe7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffe03601600081602082378035828234f58015156039578182fd5b8082525050506014600cf31ba02222222222222222222222222222222222222222222222222222222222222222a02222222222222222222222222222222222222222222222222222222222222222";
+
+/// Cost of the deployment transaction of the proxy (gas limit 100000 at 100 gwei).
+const CREATE2_PROXY_DEPLOYMENT_COST: u64 = 100_000 * 100_000_000_000;
+
+/// Deploys the proxy with its pre-signed transaction, if the chain does not have it yet.
+pub async fn ensure_proxy<P: Provider>(provider: &P) -> anyhow::Result<()> {
+    if !provider.get_code_at(CREATE2_PROXY).await?.is_empty() {
+        return Ok(());
+    }
+
+    info!("Deploying the CREATE2 deterministic deployment proxy.");
+    let cost = U256::from(CREATE2_PROXY_DEPLOYMENT_COST);
+    let balance = provider.get_balance(CREATE2_PROXY_SIGNER).await?;
+    if balance < cost {
+        let tx = TransactionRequest::default()
+            .with_to(CREATE2_PROXY_SIGNER)
+            .with_value(cost - balance);
+        let receipt = provider.send_transaction(tx).await?.get_receipt().await?;
+        anyhow::ensure!(
+            receipt.status(),
+            "Funding the CREATE2 proxy signer {} failed",
+            CREATE2_PROXY_SIGNER
+        );
+    }
+    let receipt = provider
+        .send_raw_transaction(&hex::decode(CREATE2_PROXY_DEPLOYMENT_TX)?)
+        .await?
+        .get_receipt()
+        .await?;
+    anyhow::ensure!(
+        receipt.status() && !provider.get_code_at(CREATE2_PROXY).await?.is_empty(),
+        "CREATE2 proxy deployment failed (the node must accept transactions without chain id)"
+    );
+    info!("CREATE2 proxy deployed at {}", CREATE2_PROXY);
+
+    Ok(())
+}
+
+/// Deploys a contract through the proxy and returns its address, which only depends on the salt
+/// and the init code (bytecode and constructor arguments).
+pub async fn deploy<P: Provider>(
+    provider: &P,
+    salt: B256,
+    init_code: &Bytes,
+) -> anyhow::Result<Address> {
+    let address = CREATE2_PROXY.create2_from_code(salt, init_code);
+    anyhow::ensure!(
+        provider.get_code_at(address).await?.is_empty(),
+        "A contract is already deployed at {}, use another CREATE2 salt",
+        address
+    );
+
+    let tx = TransactionRequest::default()
+        .with_to(CREATE2_PROXY)
+        .with_input([salt.as_slice(), init_code].concat());
+    let receipt = provider.send_transaction(tx).await?.get_receipt().await?;
+    anyhow::ensure!(
+        receipt.status() && !provider.get_code_at(address).await?.is_empty(),
+        "CREATE2 deployment at {} failed",
+        address
+    );
+
+    Ok(address)
+}
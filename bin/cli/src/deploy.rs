@@ -1,14 +1,21 @@
 use alloy::{
+    contract::RawCallBuilder,
     hex::ToHexExt,
-    network::EthereumWallet,
-    primitives::{Address, U256},
-    providers::{ProviderBuilder, WsConnect},
-    signers::local::LocalSigner,
+    primitives::{Address, B256, U256},
+    providers::{Provider, ProviderBuilder},
 };
 use anyhow::Context;
-use sp1_sdk::{HashableKey, Prover, ProverClient, include_elf};
-use std::str::FromStr;
+use sp1_sdk::{HashableKey, include_elf};
 use tracing::info;
+use zkopml_ml::public_values::vkey_digest;
+
+use crate::{
+    create2,
+    keys::{KeyCache, elf_hash},
+    manifest::DeploymentManifest,
+    proof_system::{ProofSystem, SP1Client, SP1Prover, check_verifier},
+    provider, signer,
+};
 
 #[derive(clap::Args, Debug, Clone)]
 pub struct DeployArgs {
@@ -19,17 +26,24 @@ pub struct DeployArgs {
     #[clap(long)]
     pub eth_node_address: String,
 
-    /// Secret key to use for deploying contracts
+    /// Signer to use for deploying contracts: hex private key, keystore:<path>, mnemonic:<path>
+    /// or remote:<url>
     #[clap(long)]
     pub deployer_key: String,
 
-    /// Secret key that owns the contracts
+    /// Signer that owns the contracts: hex private key, keystore:<path>, mnemonic:<path>
+    /// or remote:<url>
     #[clap(long)]
     pub owner_key: String,
 
     /// Address of the SP1 verifier contract
-    #[clap(long)]
-    pub sp1_verifier_address: Address,
+    #[clap(long, required_unless_present = "mock_verifier")]
+    pub sp1_verifier_address: Option<Address>,
+
+    /// Deploy the SP1 mock verifier and use it instead of the SP1 verifier contract
+    /// The contracts then accept mock proofs (`--sp1-prover mock`), for local runs only
+    #[clap(long, conflicts_with = "sp1_verifier_address")]
+    pub mock_verifier: bool,
 
     /// Challenge window for the FaultProof contract
     #[clap(long)]
@@ -38,66 +52,175 @@ pub struct DeployArgs {
     /// Response window for the FaultProof contract
     #[clap(long)]
     pub response_window: u64,
+
+    /// Bond locked by each optimistic inference response (in wei), slashed by a successful
+    /// challenge
+    #[clap(long)]
+    pub response_bond: U256,
+
+    /// Bond locked by each challenge (in wei), paid to the responder if the challenge fails
+    #[clap(long)]
+    pub challenge_bond: U256,
+
+    /// SP1 proof system the SP1 verifier contract must support
+    #[clap(long, default_value = "plonk")]
+    pub proof_system: ProofSystem,
+
+    /// Directory of the SP1 proving/verifying key cache
+    #[clap(long, default_value = ".zkopml/keys")]
+    pub key_cache_dir: String,
+
+    /// Path of the deployment manifest to write (contract addresses, deploy block and program
+    /// vkeys), read by the other commands with `--deployment`
+    #[clap(long, default_value = "deployment.json")]
+    pub manifest_path: String,
+
+    /// Deploy the contracts through the CREATE2 deterministic deployment proxy with this salt, so
+    /// their addresses only depend on the salt, the contracts and their constructor arguments
+    #[clap(long)]
+    pub create2_salt: Option<B256>,
 }
 
 const ELF: &[u8] = include_elf!("zkopml-zk");
+const AGGREGATE_ELF: &[u8] = include_elf!("zkopml-zk-aggregate");
 
 pub async fn deploy(args: DeployArgs) -> anyhow::Result<()> {
     // Initialize the owner wallet
     info!("Initializing owner wallet.");
-    let owner_signer = LocalSigner::from_str(&args.owner_key)?;
-    let owner_wallet = EthereumWallet::from(owner_signer);
-    let ws_connect = WsConnect::new(args.eth_node_address);
-    let _owner_provider = ProviderBuilder::new()
+    let owner_wallet = signer::wallet(&args.owner_key).await?;
+    let owner_provider = ProviderBuilder::new()
         .wallet(&owner_wallet)
-        .connect_ws(ws_connect.clone())
-        .await?;
-    info!("Owner address: {}", owner_wallet.default_signer().address());
-    // TODO: use owner provider
+        .connect_client(provider::rpc_client(&args.eth_node_address).await?);
+    let owner_address = owner_wallet.default_signer().address();
+    info!("Owner address: {}", owner_address);
 
     // Initialize the deployer wallet
     info!("Initializing deployer wallet.");
-    let deployer_signer = LocalSigner::from_str(&args.deployer_key)?;
-    let deployer_wallet = EthereumWallet::from(deployer_signer);
+    let deployer_wallet = signer::wallet(&args.deployer_key).await?;
     let deployer_provider = ProviderBuilder::new()
         .wallet(deployer_wallet.clone())
-        .connect_ws(ws_connect)
-        .await?;
+        .connect_client(provider::rpc_client(&args.eth_node_address).await?);
     info!(
         "Deployer address: {}",
         deployer_wallet.default_signer().address()
     );
 
-    // Deploy ModelRegistry contract
-    info!("Deploying ModelRegistry contract.");
-    let model_registry_contract =
-        zkopml_contracts::ModelRegistry::deploy(deployer_provider.clone())
-            .await
-            .context("ModelRegistry contract deployment failed")?;
+    // The contracts are deployed after the current block, their events can only be in later blocks
+    let deploy_block = deployer_provider.get_block_number().await? + 1;
+    if args.create2_salt.is_some() {
+        create2::ensure_proxy(&deployer_provider).await?;
+    }
+
+    let sp1_verifier_address = if args.mock_verifier {
+        deploy_contract(
+            &deployer_provider,
+            zkopml_contracts::SP1MockVerifier::deploy_builder(deployer_provider.clone()),
+            args.create2_salt,
+            "SP1MockVerifier",
+        )
+        .await?
+    } else if let Some(sp1_verifier_address) = args.sp1_verifier_address {
+        // Check the SP1 verifier contract before deploying the contracts using it
+        check_verifier(&deployer_provider, sp1_verifier_address, args.proof_system).await?;
+        sp1_verifier_address
+    } else {
+        anyhow::bail!(
+            "Missing the SP1 verifier: pass --sp1-verifier-address, or --mock-verifier to deploy the SP1 mock verifier"
+        );
+    };
+
+    // Generate the verification keys of the SP1 programs
+    let client = SP1Client::new(SP1Prover::Cpu).key_cache(KeyCache::new(&args.key_cache_dir));
+    let (_, vk) = client.setup(ELF).await?;
+    info!("vk: {:?}", &vk.bytes32_raw().encode_hex());
+    let (_, aggregate_vk) = client.setup(AGGREGATE_ELF).await?;
     info!(
-        "ModelRegistry contract deployed at {:?}",
-        &model_registry_contract.address()
+        "aggregation vk: {:?}",
+        &aggregate_vk.bytes32_raw().encode_hex()
     );
 
+    // Deploy ModelRegistry contract
+    let model_registry_address = deploy_contract(
+        &deployer_provider,
+        zkopml_contracts::ModelRegistry::deploy_builder(
+            deployer_provider.clone(),
+            sp1_verifier_address,
+            aggregate_vk.bytes32_raw().into(),
+            vkey_digest(&vk.hash_u32()).into(),
+            args.response_bond,
+            owner_address,
+        ),
+        args.create2_salt,
+        "ModelRegistry",
+    )
+    .await?;
+
     // Deploy FaultProof contract
-    info!("Deploying FaultProof contract.");
-    let client = ProverClient::builder().cpu().build();
-    let (_, vk) = client.setup(ELF);
-    info!("vk: {:?}", &vk.bytes32_raw().encode_hex());
-    let fault_proof_contract = zkopml_contracts::FaultProof::deploy(
-        deployer_provider.clone(),
-        model_registry_contract.address().clone(),
-        U256::from(args.challenge_window),
-        U256::from(args.response_window),
-        args.sp1_verifier_address,
-        vk.bytes32_raw().into(),
+    let fault_proof_address = deploy_contract(
+        &deployer_provider,
+        zkopml_contracts::FaultProof::deploy_builder(
+            deployer_provider.clone(),
+            model_registry_address,
+            U256::from(args.challenge_window),
+            U256::from(args.response_window),
+            sp1_verifier_address,
+            vk.bytes32_raw().into(),
+            args.challenge_bond,
+        ),
+        args.create2_salt,
+        "FaultProof",
     )
-    .await
-    .context("FaultProof contract deployment failed")?;
-    info!(
-        "FaultProof contract deployed at {:?}",
-        &fault_proof_contract.address()
+    .await?;
+
+    // The FaultProof contract locks and settles the bonds of challenges in the ModelRegistry
+    info!("Setting the FaultProof contract of the ModelRegistry.");
+    let receipt = zkopml_contracts::ModelRegistry::new(model_registry_address, &owner_provider)
+        .setFaultProof(fault_proof_address)
+        .send()
+        .await?
+        .get_receipt()
+        .await?;
+    anyhow::ensure!(
+        receipt.status(),
+        "Setting the FaultProof contract of the ModelRegistry failed"
     );
 
+    DeploymentManifest {
+        chain_id: deployer_provider.get_chain_id().await?,
+        model_registry: model_registry_address,
+        fault_proof: fault_proof_address,
+        sp1_verifier: sp1_verifier_address,
+        mock_verifier: args.mock_verifier,
+        deploy_block,
+        challenge_window: args.challenge_window,
+        response_window: args.response_window,
+        response_bond: args.response_bond,
+        challenge_bond: args.challenge_bond,
+        create2_salt: args.create2_salt,
+        program_vkey: vk.bytes32_raw().into(),
+        aggregate_program_vkey: aggregate_vk.bytes32_raw().into(),
+        program_elf_hash: elf_hash(ELF).into(),
+        aggregate_program_elf_hash: elf_hash(AGGREGATE_ELF).into(),
+    }
+    .save(&args.manifest_path)?;
+
     Ok(())
 }
+
+/// Deploys a contract, through the CREATE2 proxy if a salt is given.
+async fn deploy_contract<P: Provider>(
+    provider: &P,
+    builder: RawCallBuilder<P>,
+    create2_salt: Option<B256>,
+    name: &str,
+) -> anyhow::Result<Address> {
+    info!("Deploying {} contract.", name);
+    let address = match create2_salt {
+        Some(salt) => create2::deploy(provider, salt, builder.calldata()).await,
+        None => builder.deploy().await.map_err(Into::into),
+    }
+    .with_context(|| format!("{} contract deployment failed", name))?;
+    info!("{} contract deployed at {:?}", name, address);
+
+    Ok(address)
+}
@@ -0,0 +1,77 @@
+use alloy::hex::ToHexExt;
+use tracing::info;
+use zkopml_ml::{diff::ModelDiff, onnx::load_onnx_model};
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct DiffArgs {
+    #[arg(long, short, help = "Verbosity level (0-4)", action = clap::ArgAction::Count)]
+    pub v: u8,
+
+    /// Path to the old model file (ONNX)
+    #[clap(long)]
+    pub old_model_path: String,
+
+    /// Path to the new model file (ONNX)
+    #[clap(long)]
+    pub new_model_path: String,
+}
+
+pub async fn diff(args: DiffArgs) -> anyhow::Result<()> {
+    // Load both models
+    info!("Reading the old model file from {}", args.old_model_path);
+    let old_model = load_onnx_model(&args.old_model_path)?;
+    info!("Number of ONNX operators: {}", old_model.num_operators());
+    info!("Reading the new model file from {}", args.new_model_path);
+    let new_model = load_onnx_model(&args.new_model_path)?;
+    info!("Number of ONNX operators: {}", new_model.num_operators());
+
+    // Compare the Merkle trees leaf by leaf
+    info!("Comparing the model operators.");
+    let diff = ModelDiff::new(&old_model, &new_model)?;
+    info!("Old Merkle root hash: {:?}", diff.old_root.encode_hex());
+    info!("New Merkle root hash: {:?}", diff.new_root.encode_hex());
+
+    for operator in diff.removed.iter() {
+        info!(
+            "Removed operator {} ({}) at index {}",
+            operator.key, operator.op_type, operator.index
+        );
+    }
+    for operator in diff.added.iter() {
+        info!(
+            "Added operator {} ({}) at index {}",
+            operator.key, operator.op_type, operator.index
+        );
+    }
+    for operator in diff.modified.iter() {
+        info!(
+            "Modified operator {} ({}) at index {} -> {}: attributes changed: {}, wiring changed: {}, initializers changed: {:?}",
+            operator.new.key,
+            operator.new.op_type,
+            operator.old.index,
+            operator.new.index,
+            operator.attributes_changed,
+            operator.wiring_changed,
+            operator.initializers_changed
+        );
+    }
+
+    if diff.interface_changed {
+        info!("Graph interface (inputs, outputs, value infos) changed");
+    }
+
+    info!(
+        "Summary: {} added, {} removed, {} modified, {} unchanged",
+        diff.added.len(),
+        diff.removed.len(),
+        diff.modified.len(),
+        diff.unchanged
+    );
+    if diff.root_changed() {
+        info!("Merkle roots differ, the new model needs a new registration.");
+    } else {
+        info!("Merkle roots match, no new registration is needed.");
+    }
+
+    Ok(())
+}
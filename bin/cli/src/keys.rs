@@ -0,0 +1,98 @@
+use alloy::hex::ToHexExt;
+use sha2::{Digest, Sha256};
+use sp1_sdk::{SP1_CIRCUIT_VERSION, SP1ProvingKey, SP1VerifyingKey};
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+};
+use tracing::{info, warn};
+
+const PROVING_KEY_FILE: &str = "pk.bin";
+const VERIFYING_KEY_FILE: &str = "vk.bin";
+
+/// Persistent cache of the SP1 proving and verifying keys, so the setup of a program is done
+/// once and reused across operators and commands.
+///
+/// Keys are stored per SP1 circuit version and per program, keyed by the SHA-256 hash of the ELF
+/// (`<dir>/<circuit version>/<elf hash>/{pk,vk}.bin`), so a rebuilt program gets new keys.
+#[derive(Debug, Clone)]
+pub struct KeyCache {
+    dir: PathBuf,
+}
+
+impl KeyCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Returns the cached keys of the program, if any. Keys that cannot be decoded (e.g. written
+    /// by another SP1 SDK version) are a cache miss, and are overwritten by the next `save`.
+    pub fn load(&self, elf: &[u8]) -> anyhow::Result<Option<(SP1ProvingKey, SP1VerifyingKey)>> {
+        let dir = self.program_dir(elf);
+        let (pk_path, vk_path) = (dir.join(PROVING_KEY_FILE), dir.join(VERIFYING_KEY_FILE));
+        if !pk_path.exists() || !vk_path.exists() {
+            return Ok(None);
+        }
+
+        let pk = bincode::deserialize(&std::fs::read(pk_path)?);
+        let vk = bincode::deserialize(&std::fs::read(vk_path)?);
+        let (pk, vk) = match (pk, vk) {
+            (Ok(pk), Ok(vk)) => (pk, vk),
+            (Err(error), _) | (_, Err(error)) => {
+                warn!(
+                    "Ignoring the invalid keys of the cache {}: {}",
+                    dir.display(),
+                    error
+                );
+                return Ok(None);
+            }
+        };
+        info!("Loaded keys from the cache {}", dir.display());
+
+        Ok(Some((pk, vk)))
+    }
+
+    /// Saves the keys of the program. Each file is written next to its final path and renamed
+    /// into place, so an interrupted or concurrent save never leaves a truncated key behind. The
+    /// verifying key is written last, as `load` only reads the keys once both exist.
+    pub fn save(&self, elf: &[u8], pk: &SP1ProvingKey, vk: &SP1VerifyingKey) -> anyhow::Result<()> {
+        let dir = self.program_dir(elf);
+        std::fs::create_dir_all(&dir)?;
+        write_atomic(&dir.join(PROVING_KEY_FILE), &bincode::serialize(pk)?)?;
+        write_atomic(&dir.join(VERIFYING_KEY_FILE), &bincode::serialize(vk)?)?;
+        info!("Saved keys to the cache {}", dir.display());
+
+        Ok(())
+    }
+
+    fn program_dir(&self, elf: &[u8]) -> PathBuf {
+        self.dir
+            .join(SP1_CIRCUIT_VERSION)
+            .join(elf_hash(elf).encode_hex())
+    }
+}
+
+/// Writes the file through a temporary file of the same directory (unique to the process and the
+/// write), renamed into place.
+fn write_atomic(path: &Path, contents: &[u8]) -> anyhow::Result<()> {
+    static WRITES: AtomicU64 = AtomicU64::new(0);
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(format!(
+        ".{}.{}.tmp",
+        std::process::id(),
+        WRITES.fetch_add(1, Ordering::Relaxed)
+    ));
+    let tmp_path = path.with_file_name(tmp_name);
+    std::fs::write(&tmp_path, contents)?;
+    if let Err(error) = std::fs::rename(&tmp_path, path) {
+        let _ = std::fs::remove_file(&tmp_path);
+        return Err(error.into());
+    }
+
+    Ok(())
+}
+
+/// SHA-256 hash of a program ELF.
+pub fn elf_hash(elf: &[u8]) -> [u8; 32] {
+    Sha256::digest(elf).into()
+}
@@ -1,10 +1,25 @@
+pub mod artifact;
+pub mod bond;
+pub mod claim;
+pub mod config;
+pub mod create2;
 pub mod deploy;
+pub mod diff;
+pub mod keys;
+pub mod manifest;
+pub mod proof_system;
 pub mod prove;
+pub mod provider;
 pub mod register;
+pub mod report;
 pub mod request;
+pub mod resolve;
+pub mod signer;
 pub mod submit;
 pub mod tracing_util;
 pub mod verify;
+pub mod verify_proof;
+pub mod vkey;
 
 #[derive(clap::Parser, Debug, Clone)]
 #[command(name = "zkopml-cli")]
@@ -17,6 +32,12 @@ pub enum Cli {
     Submit(submit::SubmitArgs),
     Verify(verify::VerifyArgs),
     Prove(prove::ProveArgs),
+    Diff(diff::DiffArgs),
+    VerifyProof(verify_proof::VerifyProofArgs),
+    Resolve(resolve::ResolveArgs),
+    Vkey(vkey::VkeyArgs),
+    Bond(bond::BondArgs),
+    Claim(claim::ClaimArgs),
 }
 
 impl Cli {
@@ -28,6 +49,12 @@ impl Cli {
             Cli::Submit(args) => args.v,
             Cli::Verify(args) => args.v,
             Cli::Prove(args) => args.v,
+            Cli::Diff(args) => args.v,
+            Cli::VerifyProof(args) => args.v,
+            Cli::Resolve(args) => args.v,
+            Cli::Vkey(args) => args.v,
+            Cli::Bond(args) => args.v,
+            Cli::Claim(args) => args.v,
         }
     }
 }
@@ -1,10 +1,9 @@
-use clap::Parser;
 use zkopml_cli::Cli;
 use zkopml_cli::tracing_util::init_tracing_subscriber;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    let cli = Cli::parse();
+    let cli: Cli = zkopml_cli::config::parse()?;
     init_tracing_subscriber(cli.verbosity())?;
 
     match cli {
@@ -14,6 +13,12 @@ async fn main() -> anyhow::Result<()> {
         Cli::Submit(args) => zkopml_cli::submit::submit(args).await?,
         Cli::Verify(args) => zkopml_cli::verify::verify(args).await?,
         Cli::Prove(args) => zkopml_cli::prove::prove(args).await?,
+        Cli::Diff(args) => zkopml_cli::diff::diff(args).await?,
+        Cli::VerifyProof(args) => zkopml_cli::verify_proof::verify_proof(args).await?,
+        Cli::Resolve(args) => zkopml_cli::resolve::resolve(args).await?,
+        Cli::Vkey(args) => zkopml_cli::vkey::vkey(args).await?,
+        Cli::Bond(args) => zkopml_cli::bond::bond(args).await?,
+        Cli::Claim(args) => zkopml_cli::claim::claim(args).await?,
     }
 
     Ok(())
@@ -0,0 +1,98 @@
+use alloy::{
+    primitives::{Address, B256, U256},
+    providers::Provider,
+};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tracing::info;
+
+/// Deployment of the zk-OPML contracts, written by `deploy` and read by the other commands with
+/// `--deployment`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentManifest {
+    pub chain_id: u64,
+    pub model_registry: Address,
+    pub fault_proof: Address,
+    pub sp1_verifier: Address,
+    /// The SP1 verifier is the SP1 mock verifier (accepting mock proofs only).
+    pub mock_verifier: bool,
+    /// First block the contracts can emit events in, where event backfills start from.
+    pub deploy_block: u64,
+    /// Challenge window (in seconds) of the FaultProof.
+    pub challenge_window: u64,
+    /// Response window (in seconds) of the FaultProof.
+    pub response_window: u64,
+    /// Bond (in wei) locked by each optimistic inference response.
+    pub response_bond: U256,
+    /// Bond (in wei) locked by each challenge.
+    pub challenge_bond: U256,
+    /// Salt of the CREATE2 deployment through the deterministic deployment proxy, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub create2_salt: Option<B256>,
+    /// Verification key of the operator program, set as `PROGRAM_VKEY` of the FaultProof.
+    pub program_vkey: B256,
+    /// Verification key of the aggregation program, set as `AGGREGATE_PROGRAM_VKEY` of the
+    /// ModelRegistry.
+    pub aggregate_program_vkey: B256,
+    /// SHA-256 hash of the operator program ELF.
+    pub program_elf_hash: B256,
+    /// SHA-256 hash of the aggregation program ELF.
+    pub aggregate_program_elf_hash: B256,
+}
+
+impl DeploymentManifest {
+    pub fn save(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        info!("Saved deployment manifest to {}", path.display());
+
+        Ok(())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        Ok(serde_json::from_slice(&std::fs::read(path)?)?)
+    }
+
+    /// Loads the manifest given with `--deployment`, if any.
+    pub fn load_optional(path: Option<&String>) -> anyhow::Result<Option<Self>> {
+        let Some(path) = path else {
+            return Ok(None);
+        };
+        let manifest = Self::load(path)?;
+        info!(
+            "Using the deployment {} (ModelRegistry {}, FaultProof {})",
+            path, manifest.model_registry, manifest.fault_proof
+        );
+
+        Ok(Some(manifest))
+    }
+
+    /// Checks that the provider is connected to the chain the contracts are deployed on.
+    pub async fn check_chain_id<P: Provider>(&self, provider: &P) -> anyhow::Result<()> {
+        let chain_id = provider.get_chain_id().await?;
+        anyhow::ensure!(
+            chain_id == self.chain_id,
+            "The deployment is on chain {}, the Ethereum node is on chain {}",
+            self.chain_id,
+            chain_id
+        );
+
+        Ok(())
+    }
+}
+
+/// Returns the contract address given with `--<flag>`, or else the one of the deployment.
+pub fn contract_address(
+    address: Option<Address>,
+    deployment: Option<&DeploymentManifest>,
+    select: fn(&DeploymentManifest) -> Address,
+    flag: &str,
+) -> anyhow::Result<Address> {
+    address
+        .or_else(|| deployment.map(select))
+        .ok_or_else(|| anyhow::anyhow!("Missing --{} or --deployment", flag))
+}
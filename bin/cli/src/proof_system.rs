@@ -0,0 +1,310 @@
+use alloy::{
+    hex::ToHexExt,
+    primitives::{Address, FixedBytes},
+    providers::Provider,
+    sol,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sp1_sdk::{
+    CpuProver, ExecutionReport, NetworkProver, Prover, ProverClient, SP1ProofMode,
+    SP1ProofWithPublicValues, SP1ProvingKey, SP1PublicValues, SP1Stdin, SP1VerifyingKey,
+    network::FulfillmentStrategy,
+};
+use std::sync::Arc;
+use tracing::info;
+
+use crate::keys::KeyCache;
+
+sol!(
+    #[sol(rpc)]
+    interface ISP1VerifierGateway {
+        function routes(bytes4 selector) external view returns (address verifier, bool frozen);
+    }
+
+    #[sol(rpc)]
+    interface ISP1VerifierWithHash {
+        function VERIFIER_HASH() external pure returns (bytes32);
+    }
+);
+
+/// Cycle limit of the proof requests sent to the SP1 prover network.
+const NETWORK_CYCLE_LIMIT: u64 = 1_000_000_000;
+
+/// Type of SP1 prover.
+#[derive(clap::ValueEnum, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SP1Prover {
+    /// Local/cpu SP1 prover, generating real proofs on the machine
+    Cpu,
+    /// SP1 prover network
+    Network,
+    /// Local SP1 mock prover, generating mock proofs only accepted by the SP1 mock verifier
+    Mock,
+}
+
+/// Provers are shared with the blocking setup, execution and proving tasks.
+#[derive(Clone)]
+enum Client {
+    /// Local/cpu prover, also used for mock proofs
+    Cpu(Arc<CpuProver>),
+    Network(Arc<NetworkProver>),
+}
+
+/// SP1 prover client of the selected [`SP1Prover`].
+pub struct SP1Client {
+    prover: SP1Prover,
+    client: Client,
+    strategy: Strategy,
+    key_cache: Option<KeyCache>,
+}
+
+impl SP1Client {
+    pub fn new(prover: SP1Prover) -> Self {
+        let client = match prover {
+            SP1Prover::Cpu => {
+                info!("Using the local/cpu SP1 prover.");
+                Client::Cpu(Arc::new(ProverClient::builder().cpu().build()))
+            }
+            SP1Prover::Network => {
+                info!("Using the network SP1 prover.");
+                Client::Network(Arc::new(ProverClient::builder().network().build()))
+            }
+            SP1Prover::Mock => {
+                info!("Using the mock SP1 prover, proofs are only accepted by the mock verifier.");
+                Client::Cpu(Arc::new(ProverClient::builder().mock().build()))
+            }
+        };
+        Self {
+            prover,
+            client,
+            strategy: Strategy::Hosted,
+            key_cache: None,
+        }
+    }
+
+    pub fn prover(&self) -> SP1Prover {
+        self.prover
+    }
+
+    /// Fulfillment strategy of the proof requests, only applies to the prover network.
+    pub fn strategy(mut self, strategy: Strategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Cache of the program keys, reused by [`SP1Client::setup`].
+    pub fn key_cache(mut self, key_cache: KeyCache) -> Self {
+        self.key_cache = Some(key_cache);
+        self
+    }
+
+    /// Generates the keys of the program, or loads them from the key cache.
+    ///
+    /// The setup runs on a blocking thread, so it does not stall the async runtime.
+    pub async fn setup(
+        &self,
+        elf: &'static [u8],
+    ) -> anyhow::Result<(SP1ProvingKey, SP1VerifyingKey)> {
+        let (client, key_cache) = (self.client.clone(), self.key_cache.clone());
+        tokio::task::spawn_blocking(move || {
+            if let Some(key_cache) = key_cache.as_ref()
+                && let Some(keys) = key_cache.load(elf)?
+            {
+                return Ok(keys);
+            }
+
+            let (pk, vk) = match &client {
+                Client::Cpu(client) => client.setup(elf),
+                Client::Network(client) => client.setup(elf),
+            };
+            info!("Generated keys (setup)");
+            if let Some(key_cache) = key_cache.as_ref() {
+                key_cache.save(elf, &pk, &vk)?;
+            }
+
+            Ok((pk, vk))
+        })
+        .await?
+    }
+
+    /// Executes the program locally, without proving.
+    ///
+    /// The program runs on a blocking thread, so it does not stall the async runtime.
+    pub async fn execute(
+        &self,
+        elf: &'static [u8],
+        stdin: &SP1Stdin,
+    ) -> anyhow::Result<(SP1PublicValues, ExecutionReport)> {
+        let (client, stdin) = (self.client.clone(), stdin.clone());
+        tokio::task::spawn_blocking(move || {
+            let output = match &client {
+                Client::Cpu(client) => client.execute(elf, &stdin).run()?,
+                Client::Network(client) => client.execute(elf, &stdin).run()?,
+            };
+            Ok(output)
+        })
+        .await?
+    }
+
+    /// Registers the program on the prover network, local provers do not need it.
+    pub async fn register_program(&self, vk: &SP1VerifyingKey, elf: &[u8]) -> anyhow::Result<()> {
+        if let Client::Network(client) = &self.client {
+            let program_hash = client.register_program(vk, elf).await?;
+            info!("Registered program with hash: {:?}", program_hash);
+        }
+        Ok(())
+    }
+
+    /// Generates a proof of the given mode.
+    ///
+    /// Local proofs are generated on a blocking thread, so they do not stall the async runtime.
+    pub async fn prove(
+        &self,
+        pk: &SP1ProvingKey,
+        stdin: &SP1Stdin,
+        mode: SP1ProofMode,
+    ) -> anyhow::Result<SP1ProofWithPublicValues> {
+        let proof = match &self.client {
+            Client::Cpu(client) => {
+                let (client, pk, stdin) = (client.clone(), pk.clone(), stdin.clone());
+                tokio::task::spawn_blocking(move || client.prove(&pk, &stdin).mode(mode).run())
+                    .await??
+            }
+            // The programs are executed locally before proving, so the network simulation is skipped
+            Client::Network(client) => {
+                client
+                    .prove(pk, stdin)
+                    .strategy(self.strategy.into())
+                    .cycle_limit(NETWORK_CYCLE_LIMIT)
+                    .skip_simulation(true)
+                    .mode(mode)
+                    .run_async()
+                    .await?
+            }
+        };
+        Ok(proof)
+    }
+
+    pub fn verify(
+        &self,
+        proof: &SP1ProofWithPublicValues,
+        vk: &SP1VerifyingKey,
+    ) -> anyhow::Result<()> {
+        match &self.client {
+            Client::Cpu(client) => client.verify(proof, vk)?,
+            Client::Network(client) => client.verify(proof, vk)?,
+        }
+        Ok(())
+    }
+}
+
+/// SP1 proof system of the generated proofs.
+#[derive(clap::ValueEnum, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ProofSystem {
+    /// PLONK proof (verifiable on-chain)
+    Plonk,
+    /// Groth16 proof (verifiable on-chain, cheaper to verify)
+    Groth16,
+    /// Compressed STARK proof (not verifiable on-chain, can be aggregated)
+    Compressed,
+    /// Core STARK proof (not verifiable on-chain)
+    Core,
+}
+
+impl ProofSystem {
+    pub fn mode(&self) -> SP1ProofMode {
+        match self {
+            ProofSystem::Plonk => SP1ProofMode::Plonk,
+            ProofSystem::Groth16 => SP1ProofMode::Groth16,
+            ProofSystem::Compressed => SP1ProofMode::Compressed,
+            ProofSystem::Core => SP1ProofMode::Core,
+        }
+    }
+
+    /// Selector of the on-chain SP1 verifier of the proof system (the first 4 bytes of the
+    /// verifier hash, which are also the first 4 bytes of the proof), if the proof can be verified
+    /// on-chain.
+    pub fn verifier_selector(&self) -> Option<[u8; 4]> {
+        let vk: &[u8] = match self {
+            ProofSystem::Plonk => sp1_verifier::PLONK_VK_BYTES,
+            ProofSystem::Groth16 => sp1_verifier::GROTH16_VK_BYTES,
+            ProofSystem::Compressed | ProofSystem::Core => return None,
+        };
+        let hash = Sha256::digest(vk);
+        Some([hash[0], hash[1], hash[2], hash[3]])
+    }
+}
+
+/// Fulfillment strategy of the proof requests sent to the SP1 prover network.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    /// Proofs are generated by the hosted prover
+    Hosted,
+    /// Proofs are generated on reserved capacity
+    Reserved,
+    /// Proofs are auctioned to the provers of the network
+    Auction,
+}
+
+impl From<Strategy> for FulfillmentStrategy {
+    fn from(strategy: Strategy) -> Self {
+        match strategy {
+            Strategy::Hosted => FulfillmentStrategy::Hosted,
+            Strategy::Reserved => FulfillmentStrategy::Reserved,
+            Strategy::Auction => FulfillmentStrategy::Auction,
+        }
+    }
+}
+
+/// Checks that the deployed SP1 verifier accepts proofs of the proof system, before spending
+/// time on proving.
+///
+/// The verifier is either an SP1 verifier gateway (routing proofs by their selector) or a single
+/// SP1 verifier.
+pub async fn check_verifier<P: Provider>(
+    provider: &P,
+    verifier: Address,
+    proof_system: ProofSystem,
+) -> anyhow::Result<()> {
+    let selector = proof_system
+        .verifier_selector()
+        .ok_or_else(|| anyhow::anyhow!("{:?} proofs cannot be verified on-chain", proof_system))?;
+    info!(
+        "Checking that SP1 verifier {} supports {:?} proofs (selector 0x{})",
+        verifier,
+        proof_system,
+        selector.encode_hex()
+    );
+
+    let gateway = ISP1VerifierGateway::new(verifier, provider);
+    if let Ok(route) = gateway.routes(FixedBytes(selector)).call().await {
+        anyhow::ensure!(
+            route.verifier != Address::ZERO,
+            "SP1 verifier gateway {} has no route for {:?} proofs",
+            verifier,
+            proof_system
+        );
+        anyhow::ensure!(
+            !route.frozen,
+            "SP1 verifier gateway {} route for {:?} proofs is frozen",
+            verifier,
+            proof_system
+        );
+        return Ok(());
+    }
+
+    let verifier_hash = ISP1VerifierWithHash::new(verifier, provider)
+        .VERIFIER_HASH()
+        .call()
+        .await?;
+    anyhow::ensure!(
+        verifier_hash[..4] == selector,
+        "SP1 verifier {} does not verify {:?} proofs",
+        verifier,
+        proof_system
+    );
+
+    Ok(())
+}
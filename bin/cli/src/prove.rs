@@ -1,20 +1,38 @@
-use alloy::hex::ToHexExt;
+use alloy::{
+    hex::ToHexExt,
+    primitives::{Address, U256},
+    providers::ProviderBuilder,
+};
 use candle_core::Tensor;
 use candle_onnx::eval::{get_tensor, simple_eval_one};
-use sp1_sdk::{Prover, ProverClient, SP1Stdin, include_elf, network::FulfillmentStrategy};
-use std::collections::HashMap;
-use tracing::info;
+use futures_util::future::try_join_all;
+use sp1_sdk::{
+    HashableKey, SP1Proof, SP1ProofMode, SP1ProofWithPublicValues, SP1Stdin, SP1VerifyingKey,
+    include_elf,
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    ops::RangeInclusive,
+    time::Instant,
+};
+use tracing::{info, warn};
 use zkopml_ml::{
-    data::{extract_input_data, tensor_hash},
-    merkle::{MerkleTreeHash, ModelMerkleTree},
-    onnx::load_onnx_model,
+    data::extract_input_data,
+    merkle::ModelMerkleTree,
+    onnx::{Model, load_onnx_model},
+    public_values::{AggregatePublicValues, ProofContext, PublicValues},
+    witness::{AggregationWitness, OperatorWitness},
 };
 
-#[derive(clap::ValueEnum, Debug, Clone, PartialEq, Eq)]
-pub enum SP1Prover {
-    Cpu,
-    Network,
-}
+use crate::{
+    artifact::{Program, ProofArtifact, aggregate_dir, operator_dir},
+    keys::KeyCache,
+    manifest::DeploymentManifest,
+    proof_system::{ProofSystem, SP1Client, SP1Prover, Strategy},
+    provider,
+    report::{OperatorReport, ProveReport},
+    vkey::{check_aggregate_program_vkey, check_program_vkey},
+};
 
 #[derive(clap::Args, Debug, Clone)]
 pub struct ProveArgs {
@@ -31,19 +49,116 @@ pub struct ProveArgs {
 
     /// Index of the ONNX operator to prove
     /// If not provided, the prover will prove all operators
-    #[clap(long)]
+    #[clap(long, conflicts_with = "aggregate")]
     pub operator_index: Option<usize>,
 
+    /// Prove the operators independently and aggregate the proofs into one (SP1 recursion)
+    /// The aggregated proof attests to the state transition from the input state of the first
+    /// operator to the output state of the last operator
+    #[clap(long)]
+    pub aggregate: bool,
+
+    /// Index of the first ONNX operator to aggregate (defaults to the first operator)
+    #[clap(long, requires = "aggregate")]
+    pub first_operator: Option<usize>,
+
+    /// Index of the last ONNX operator to aggregate (defaults to the last operator)
+    #[clap(long, requires = "aggregate")]
+    pub last_operator: Option<usize>,
+
     /// Type of SP1 prover
     /// - `cpu`: Use the local/cpu SP1 prover
-    ///   - Note: When proving with cpu, this will not actually generate all proofs,
-    ///     but will only output the number of cycles for each operator.
     /// - `network`: Use the network SP1 prover
+    /// - `mock`: Use the local SP1 mock prover (proofs only accepted by the SP1 mock verifier)
     #[clap(long, default_value = "cpu")]
     pub sp1_prover: SP1Prover,
+
+    /// Generate the proofs with the cpu or mock SP1 prover, which otherwise only execute the SP1
+    /// program and output the number of cycles for each operator (the network prover always
+    /// generates proofs, unless `--execute-only` is given)
+    #[clap(long, conflicts_with = "execute_only")]
+    pub prove: bool,
+
+    /// Only execute the SP1 program and output the number of cycles for each operator, without
+    /// generating proofs
+    #[clap(long, conflicts_with = "aggregate")]
+    pub execute_only: bool,
+
+    /// SP1 proof system of the generated proof (the operator proofs are always compressed when
+    /// aggregating)
+    #[clap(long, default_value = "plonk")]
+    pub proof_system: ProofSystem,
+
+    /// Fulfillment strategy of the SP1 prover network
+    #[clap(long, default_value = "hosted")]
+    pub strategy: Strategy,
+
+    /// Directory of the SP1 proving/verifying key cache
+    #[clap(long, default_value = ".zkopml/keys")]
+    pub key_cache_dir: String,
+
+    /// Directory to save the generated proofs to (with their verification key and metadata)
+    #[clap(long)]
+    pub output_dir: Option<String>,
+
+    /// Path of the per-operator cycle and timing report (CSV if the path ends with `.csv`, JSON
+    /// otherwise)
+    #[clap(long, conflicts_with = "aggregate")]
+    pub report_path: Option<String>,
+
+    /// Challenge window (in seconds) of the zk-OPML time estimate of the report (defaults to the
+    /// one of the deployment, otherwise to 120)
+    #[clap(long)]
+    pub challenge_window: Option<u64>,
+
+    /// Response window (in seconds) of the zk-OPML time estimate of the report (defaults to the
+    /// one of the deployment, otherwise to 30)
+    #[clap(long)]
+    pub response_window: Option<u64>,
+
+    /// Path of the deployment manifest written by `deploy`, to bind the proofs to its chain and
+    /// contracts and to take the windows of the report from
+    #[clap(long)]
+    pub deployment: Option<String>,
+
+    /// Address of the Ethereum node endpoint to use, to check the local programs against the
+    /// contract the proof is bound to (FaultProof, or ModelRegistry when aggregating) before proving.
+    /// Without it, the check is skipped (with a warning) and a mismatching proof is only rejected
+    /// on-chain
+    #[clap(long)]
+    pub eth_node_address: Option<String>,
+
+    /// Chain id of the challenge (or full-ZK inference) the proof is bound to, defaults to the one
+    /// of the deployment, otherwise to 0
+    #[clap(long)]
+    pub chain_id: Option<u64>,
+
+    /// Address of the FaultProof contract of the challenge the proof is bound to, defaults to the
+    /// one of the deployment, otherwise to the zero address
+    #[clap(long, conflicts_with = "aggregate")]
+    pub fault_proof_address: Option<Address>,
+
+    /// Id of the challenge the proof is bound to
+    #[clap(long, default_value = "0", conflicts_with = "aggregate")]
+    pub challenge_id: U256,
+
+    /// Address of the ModelRegistry contract of the full-ZK inference the aggregated proof is
+    /// bound to, defaults to the one of the deployment, otherwise to the zero address
+    #[clap(long, requires = "aggregate")]
+    pub model_registry_address: Option<Address>,
+
+    /// Id of the full-ZK inference the aggregated proof is bound to (defaults to 0)
+    #[clap(long, requires = "aggregate")]
+    pub inference_id: Option<U256>,
 }
 
 const ELF: &[u8] = include_elf!("zkopml-zk");
+const AGGREGATE_ELF: &[u8] = include_elf!("zkopml-zk-aggregate");
+
+/// Challenge window (in seconds) of the report without a deployment.
+const DEFAULT_CHALLENGE_WINDOW: u64 = 120;
+/// Response window (in seconds) of the report without a deployment.
+const DEFAULT_RESPONSE_WINDOW: u64 = 30;
 
 pub async fn prove(args: ProveArgs) -> anyhow::Result<()> {
     // Load the model and perform the inference
@@ -59,147 +174,422 @@ pub async fn prove(args: ProveArgs) -> anyhow::Result<()> {
     let merkle_tree = ModelMerkleTree::new(nodes.clone(), model.graph().unwrap());
     info!("Merkle root hash: {:?}", merkle_tree.root().encode_hex());
 
+    // Bind the proofs to the given challenge (or full-ZK inference when aggregating), on the chain
+    // and contracts of the deployment unless given otherwise
+    let deployment = DeploymentManifest::load_optional(args.deployment.as_ref())?;
+    let chain_id = args
+        .chain_id
+        .or(deployment.as_ref().map(|manifest| manifest.chain_id));
+
+    if args.aggregate {
+        let model_registry = args
+            .model_registry_address
+            .or(deployment.as_ref().map(|manifest| manifest.model_registry));
+        let context = ProofContext::inference(
+            chain_id.unwrap_or_default(),
+            model_registry.unwrap_or_default(),
+            args.inference_id.unwrap_or_default(),
+        );
+        return aggregate(&args, &model, &merkle_tree, context).await;
+    }
+
+    let fault_proof = args
+        .fault_proof_address
+        .or(deployment.as_ref().map(|manifest| manifest.fault_proof));
+    let context = ProofContext::challenge(
+        chain_id.unwrap_or_default(),
+        fault_proof.unwrap_or_default(),
+        args.challenge_id,
+    );
+
     if let Some(operator_index) = args.operator_index {
+        if operator_index >= model.num_operators() {
+            anyhow::bail!(
+                "Operator index {} is out of range, the model has {} operators",
+                operator_index,
+                model.num_operators()
+            );
+        }
         nodes = vec![nodes[operator_index].clone()];
         nodes_indices = vec![operator_index];
     }
 
+    let client = SP1Client::new(args.sp1_prover)
+        .strategy(args.strategy)
+        .key_cache(KeyCache::new(&args.key_cache_dir));
+
+    let generate_proofs =
+        !args.execute_only && (args.prove || args.sp1_prover == SP1Prover::Network);
+
+    // Check that the local program is the deployed one, before spending time on proving
+    if let Some(eth_node_address) = args.eth_node_address.as_ref() {
+        let provider =
+            ProviderBuilder::new().connect_client(provider::rpc_client(eth_node_address).await?);
+        let (_, vk) = client.setup(ELF).await?;
+        check_program_vkey(&provider, context.verifier, &vk).await?;
+    } else if generate_proofs {
+        warn_vkey_check_skipped();
+    }
+    let keys = if !generate_proofs {
+        info!("Only executing the SP1 program, no proofs will be generated.");
+        None
+    } else {
+        let (pk, vk) = client.setup(ELF).await?;
+        client.register_program(&vk, ELF).await?;
+        Some((pk, vk))
+    };
+
+    let mut reports = Vec::new();
     for (node, node_index) in nodes.iter().zip(nodes_indices.iter()) {
-        // Create SP1 proof of execution
-        let mut stdin = SP1Stdin::new();
-        stdin.write(&merkle_tree.root());
-
-        let leaf_indices = vec![*node_index];
-        stdin.write(&leaf_indices);
-
-        let leaf_hashes = merkle_tree.leaves_hashes(leaf_indices.clone());
-        stdin.write(&leaf_hashes);
-
-        let total_leaves = merkle_tree.total_leaves();
-        stdin.write(&total_leaves);
-
-        let merkle_proof: Vec<u8> = merkle_tree.prove(leaf_indices).to_bytes();
-        stdin.write(&merkle_proof);
-
-        let mut inputs: HashMap<String, Tensor> = HashMap::new();
-        for t in model.graph().clone().unwrap().initializer.iter() {
-            let tensor = get_tensor(t, t.name.as_str())?;
-            inputs.insert(t.name.to_string(), tensor);
-        }
-        let input_data =
-            extract_input_data(&std::fs::read_to_string(args.input_data_path.clone())?)?;
-        model.prepare_inputs(&mut inputs, input_data)?;
+        // Compute the inference state before the operator
+        let mut state = initial_state(&model, &args.input_data_path)?;
         for j in 0..*node_index {
             let node = model.get_node(j).unwrap();
-            simple_eval_one(&node, &mut inputs)?;
+            simple_eval_one(&node, &mut state)?;
         }
-        let mut input_hashes = HashMap::new();
-        for (name, tensor) in inputs.iter() {
-            let hash = tensor_hash(tensor);
-            input_hashes.insert(name.clone(), hash);
+
+        // Create SP1 proof of execution
+        let witness = OperatorWitness::builder(&model, &merkle_tree)
+            .operator_index(*node_index)
+            .state(&state)
+            .context(context.clone())
+            .build()?;
+        let mut stdin = SP1Stdin::new();
+        stdin.write(&witness);
+
+        info!(
+            "Executing the SP1 program. Proving ONNX operator: {:?}",
+            node
+        );
+
+        let start = Instant::now();
+        let (public_values, report) = client.execute(ELF, &stdin).await?;
+        let execution_secs = start.elapsed().as_secs_f64();
+        info!(
+            "Executed program with {} cycles",
+            report.total_instruction_count()
+        );
+        let sections: BTreeMap<String, u64> = report
+            .cycle_tracker
+            .iter()
+            .map(|(section, cycles)| (section.clone(), *cycles))
+            .collect();
+        for (section, cycles) in &sections {
+            info!("Cycles of {}: {}", section, cycles);
         }
-        inputs.retain(|k: &String, _| node.input.contains(k));
-        let mut inputs_raw: HashMap<String, Tensor> = HashMap::new();
-        for (name, tensor) in inputs.iter() {
-            let mut init = false;
-            for t in model.graph().unwrap().initializer.iter() {
-                if name == &t.name {
-                    let mut input_name = name.clone();
-                    input_name.push_str("graph_initializer");
-                    inputs_raw.insert(input_name, tensor.clone());
-                    init = true;
-                    break;
-                }
-            }
-            if !init {
-                inputs_raw.insert(name.clone(), tensor.clone());
-            }
+
+        let input_shapes: Vec<Vec<usize>> = node
+            .input
+            .iter()
+            .filter_map(|name| state.get(name))
+            .map(|tensor| tensor.dims().to_vec())
+            .collect();
+        reports.push(OperatorReport {
+            operator_index: *node_index,
+            name: node.name.clone(),
+            op_type: node.op_type.clone(),
+            input_elements: input_shapes
+                .iter()
+                .map(|shape| shape.iter().product::<usize>())
+                .sum(),
+            input_shapes,
+            cycles: report.total_instruction_count(),
+            sections,
+            execution_secs,
+            proving_secs: None,
+        });
+
+        info!("Raw public values: {:?}", public_values.raw());
+
+        let public_values = PublicValues::decode(public_values.as_slice())?;
+
+        info!("Returned public values:");
+        info!(
+            "Context: chain id {}, verifier {}, domain {}, id {}",
+            public_values.context.chainId,
+            public_values.context.verifier,
+            public_values.context.domain,
+            public_values.context.id
+        );
+        info!("Merkle root: {:?}", public_values.merkleRoot.encode_hex());
+        info!("Number of operators: {}", public_values.numOperators);
+        info!("Operator index: {}", public_values.operatorIndex);
+        info!(
+            "Inputs hash: {:?}",
+            public_values.inputDataHash.encode_hex()
+        );
+        info!(
+            "Outputs hash: {:?}",
+            public_values.outputDataHash.encode_hex()
+        );
+
+        let Some((pk, vk)) = keys.as_ref() else {
+            continue;
+        };
+
+        let start = Instant::now();
+        let proof = client.prove(pk, &stdin, args.proof_system.mode()).await?;
+        let proving_secs = start.elapsed().as_secs_f64();
+        info!("Generated proof in {:.2} s", proving_secs);
+        if let Some(report) = reports.last_mut() {
+            report.proving_secs = Some(proving_secs);
         }
-        stdin.write(&inputs_raw);
-        stdin.write(&input_hashes);
 
-        stdin.write(&node);
+        let proof_bytes = proof.bytes();
+        info!("Proof: 0x{}", proof_bytes.encode_hex());
+
+        client.verify(&proof, vk)?;
+        info!("Verified proof");
+
+        if let Some(output_dir) = args.output_dir.as_ref() {
+            ProofArtifact::new(
+                proof,
+                vk.clone(),
+                Program::Operator,
+                args.sp1_prover,
+                args.proof_system,
+            )?
+            .save(operator_dir(output_dir, *node_index))?;
+        }
+    }
 
-        if args.sp1_prover == SP1Prover::Cpu {
-            info!(
-                "Using the local/cpu SP1 prover. This will not actually generate all proofs, but will only output the number of cycles for each operator."
-            );
-            let client = ProverClient::builder().cpu().build();
-            info!(
-                "Executing the SP1 program. Proving ONNX operator: {:?}",
-                node
-            );
+    let challenge_window = args
+        .challenge_window
+        .or(deployment
+            .as_ref()
+            .map(|manifest| manifest.challenge_window))
+        .unwrap_or(DEFAULT_CHALLENGE_WINDOW);
+    let response_window = args
+        .response_window
+        .or(deployment.as_ref().map(|manifest| manifest.response_window))
+        .unwrap_or(DEFAULT_RESPONSE_WINDOW);
+    let report = ProveReport::new(
+        reports,
+        model.num_operators(),
+        challenge_window,
+        response_window,
+    )?;
+    report.log_summary();
+    if let Some(report_path) = args.report_path.as_ref() {
+        report.save(report_path)?;
+    }
 
-            let (mut public_values, report) = client.execute(ELF, &stdin).run().unwrap();
-            info!(
-                "Executed program with {} cycles",
-                report.total_instruction_count()
-            );
+    Ok(())
+}
 
-            info!("Raw public values: {:?}", public_values.raw());
-
-            let merkle_root_ret = public_values.read::<MerkleTreeHash>();
-            let leaf_indices_ret = public_values.read::<Vec<usize>>();
-            let inputs_hash = public_values.read::<[u8; 32]>();
-            let outputs_hash = public_values.read::<[u8; 32]>();
-
-            info!("Returned public values:");
-            info!("Merkle root: {:?}", merkle_root_ret.encode_hex());
-            info!("Leaf indices: {:?}", leaf_indices_ret);
-            info!("Inputs hash: {:?}", inputs_hash.encode_hex());
-            info!("Outputs hash: {:?}", outputs_hash.encode_hex());
-
-            // let (_, vk) = client.setup(ELF);
-            // info!("Generated keys (setup), vk: {:?}", vk.bytes32());
-        } else {
-            info!("Using the network SP1 prover.");
-            let client = ProverClient::builder().network().build();
-            info!(
-                "Executing the SP1 program. Proving ONNX operator: {:?}",
-                node
-            );
+fn warn_vkey_check_skipped() {
+    warn!(
+        "No --eth-node-address given: the local SP1 programs are NOT checked against the deployed contracts, the proofs are rejected on-chain if they do not match (see `vkey`)"
+    );
+}
 
-            let (mut public_values, report) = client.execute(ELF, &stdin).run().unwrap();
-            info!(
-                "Executed program with {} cycles",
-                report.total_instruction_count()
-            );
+/// Proves consecutive operators independently (in parallel) and aggregates the proofs into one
+/// proof of the state transition from the first to the last operator.
+async fn aggregate(
+    args: &ProveArgs,
+    model: &Model,
+    merkle_tree: &ModelMerkleTree,
+    context: ProofContext,
+) -> anyhow::Result<()> {
+    let num_operators = model.num_operators();
+    let first_operator = args.first_operator.unwrap_or(0);
+    let last_operator = args
+        .last_operator
+        .unwrap_or(num_operators.saturating_sub(1));
+    if first_operator > last_operator || last_operator >= num_operators {
+        anyhow::bail!(
+            "Operator range {}..={} is out of range, the model has {} operators",
+            first_operator,
+            last_operator,
+            num_operators
+        );
+    }
 
-            info!("Raw public values: {:?}", public_values.raw());
+    let state = initial_state(model, &args.input_data_path)?;
+    let client = SP1Client::new(args.sp1_prover)
+        .strategy(args.strategy)
+        .key_cache(KeyCache::new(&args.key_cache_dir));
+
+    // Check that the local programs are the deployed ones, before spending time on proving
+    if let Some(eth_node_address) = args.eth_node_address.as_ref() {
+        let provider =
+            ProviderBuilder::new().connect_client(provider::rpc_client(eth_node_address).await?);
+        let (_, vk) = client.setup(ELF).await?;
+        let (_, aggregate_vk) = client.setup(AGGREGATE_ELF).await?;
+        check_aggregate_program_vkey(&provider, context.verifier, &vk, &aggregate_vk).await?;
+    } else {
+        warn_vkey_check_skipped();
+    }
 
-            let merkle_root_ret = public_values.read::<MerkleTreeHash>();
-            let leaf_indices_ret = public_values.read::<Vec<usize>>();
-            let inputs_hash = public_values.read::<[u8; 32]>();
-            let outputs_hash = public_values.read::<[u8; 32]>();
+    let (proof, vk) = prove_aggregate(
+        &client,
+        model,
+        merkle_tree,
+        state,
+        first_operator..=last_operator,
+        context,
+        args.proof_system,
+    )
+    .await?;
+
+    let public_values = AggregatePublicValues::decode(proof.public_values.as_slice())?;
+    info!("Returned aggregate public values:");
+    info!(
+        "Context: chain id {}, verifier {}, domain {}, id {}",
+        public_values.context.chainId,
+        public_values.context.verifier,
+        public_values.context.domain,
+        public_values.context.id
+    );
+    info!(
+        "Operator program vkey: {:?}",
+        public_values.operatorVkey.encode_hex()
+    );
+    info!("Merkle root: {:?}", public_values.merkleRoot.encode_hex());
+    info!("Number of operators: {}", public_values.numOperators);
+    info!(
+        "Operators: {} to {}",
+        public_values.firstOperator, public_values.lastOperator
+    );
+    info!(
+        "Inputs hash: {:?}",
+        public_values.inputDataHash.encode_hex()
+    );
+    info!(
+        "Outputs hash: {:?}",
+        public_values.outputDataHash.encode_hex()
+    );
+
+    info!("Proof: 0x{}", proof.bytes().encode_hex());
+
+    if let Some(output_dir) = args.output_dir.as_ref() {
+        ProofArtifact::new(
+            proof,
+            vk,
+            Program::Aggregate,
+            args.sp1_prover,
+            args.proof_system,
+        )?
+        .save(aggregate_dir(output_dir, first_operator, last_operator))?;
+    }
 
-            info!("Returned public values:");
-            info!("Merkle root: {:?}", merkle_root_ret.encode_hex());
-            info!("Leaf indices: {:?}", leaf_indices_ret);
-            info!("Inputs hash: {:?}", inputs_hash.encode_hex());
-            info!("Outputs hash: {:?}", outputs_hash.encode_hex());
+    Ok(())
+}
 
-            let (pk, vk) = client.setup(ELF);
-            info!("Generated keys (setup)");
+/// Proves the `operators` independently (in parallel on the
+/// network prover) and aggregates the proofs with SP1 recursion into one verified proof of the given
+/// proof system. Returns the aggregated proof and the verification key of the aggregation program.
+///
+/// `state` is the inference state before the first operator of the model.
+pub async fn prove_aggregate(
+    client: &SP1Client,
+    model: &Model,
+    merkle_tree: &ModelMerkleTree,
+    mut state: HashMap<String, Tensor>,
+    operators: RangeInclusive<usize>,
+    context: ProofContext,
+    proof_system: ProofSystem,
+) -> anyhow::Result<(SP1ProofWithPublicValues, SP1VerifyingKey)> {
+    let (first_operator, last_operator) = (*operators.start(), *operators.end());
+    // Mock operator proofs cannot be verified with SP1 recursion
+    anyhow::ensure!(
+        client.prover() != SP1Prover::Mock,
+        "Aggregation does not support the mock SP1 prover"
+    );
+    anyhow::ensure!(
+        first_operator <= last_operator && last_operator < model.num_operators(),
+        "Invalid operator range {}..={}",
+        first_operator,
+        last_operator
+    );
+
+    // Build the witnesses of all operators in a single pass over the inference
+    let mut stdins = Vec::new();
+    for i in 0..=last_operator {
+        if i >= first_operator {
+            let witness = OperatorWitness::builder(model, merkle_tree)
+                .operator_index(i)
+                .state(&state)
+                .context(context.clone())
+                .build()?;
+            let mut stdin = SP1Stdin::new();
+            stdin.write(&witness);
+            stdins.push(stdin);
+        }
+        if i < last_operator {
+            simple_eval_one(&model.get_node(i).unwrap(), &mut state)?;
+        }
+    }
 
-            let program_hash = client.register_program(&vk, ELF).await?;
-            info!("Registered program with hash: {:?}", program_hash);
+    let (pk, vk) = client.setup(ELF).await?;
+    let (aggregate_pk, aggregate_vk) = client.setup(AGGREGATE_ELF).await?;
+
+    client.register_program(&vk, ELF).await?;
+    client
+        .register_program(&aggregate_vk, AGGREGATE_ELF)
+        .await?;
+
+    // Compressed proofs can be verified inside the aggregation program
+    info!(
+        "Proving ONNX operators {} to {}",
+        first_operator, last_operator
+    );
+    let proofs = if client.prover() == SP1Prover::Network {
+        try_join_all(
+            stdins
+                .iter()
+                .map(|stdin| client.prove(&pk, stdin, SP1ProofMode::Compressed)),
+        )
+        .await?
+    } else {
+        // A local proof already uses all the cores
+        let mut proofs = Vec::new();
+        for stdin in &stdins {
+            proofs.push(client.prove(&pk, stdin, SP1ProofMode::Compressed).await?);
+        }
+        proofs
+    };
+    info!("Generated {} operator proofs", proofs.len());
+
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&AggregationWitness {
+        operator_vkey: vk.hash_u32(),
+        public_values: proofs
+            .iter()
+            .map(|proof| proof.public_values.to_vec())
+            .collect(),
+    });
+    for proof in proofs {
+        let SP1Proof::Compressed(proof) = proof.proof else {
+            anyhow::bail!("Expected a compressed operator proof");
+        };
+        stdin.write_proof(*proof, vk.vk.clone());
+    }
 
-            let proof = client
-                .prove(&pk, &stdin)
-                .strategy(FulfillmentStrategy::Hosted)
-                .plonk()
-                .run()
-                .unwrap();
-            info!("Generated proof");
+    info!("Aggregating the operator proofs.");
+    let proof = client
+        .prove(&aggregate_pk, &stdin, proof_system.mode())
+        .await?;
+    info!("Generated aggregated proof");
 
-            let proof_bytes = proof.bytes();
-            info!("Proof: 0x{}", proof_bytes.encode_hex());
+    client.verify(&proof, &aggregate_vk)?;
+    info!("Verified aggregated proof");
 
-            client.verify(&proof, &vk).expect("verification failed");
+    Ok((proof, aggregate_vk))
+}
 
-            info!("Verified proof");
-        }
+/// Inference state before the first operator (graph initializers and input data).
+pub fn initial_state(
+    model: &Model,
+    input_data_path: &str,
+) -> anyhow::Result<HashMap<String, Tensor>> {
+    let mut state: HashMap<String, Tensor> = HashMap::new();
+    for t in model.graph().clone().unwrap().initializer.iter() {
+        let tensor = get_tensor(t, t.name.as_str())?;
+        state.insert(t.name.to_string(), tensor);
     }
+    let input_data = extract_input_data(&std::fs::read_to_string(input_data_path)?)?;
+    model.prepare_inputs(&mut state, input_data)?;
 
-    Ok(())
+    Ok(state)
 }
@@ -0,0 +1,55 @@
+use alloy::{
+    providers::WsConnect,
+    rpc::client::{ClientBuilder, RpcClient},
+    transports::layers::RetryBackoffLayer,
+};
+use std::time::Duration;
+
+/// Maximum number of retries of a request failing with a transient error (rate limit, node
+/// unavailable).
+const MAX_RETRIES: u32 = 10;
+
+/// Backoff (in milliseconds) before the first retry of a request, doubled on each retry.
+const INITIAL_BACKOFF_MS: u64 = 500;
+
+/// Compute units per second of the node, used to space out retries of rate-limited requests.
+const COMPUTE_UNITS_PER_SECOND: u64 = 330;
+
+/// Maximum number of attempts to reconnect a dropped WebSocket, unbounded in practice: the
+/// daemons wait for the node to come back however long it is down, as a provider that gave up
+/// would leave them hanging without events.
+const WS_MAX_RECONNECTS: u32 = u32::MAX;
+
+/// Interval between two attempts to reconnect a dropped WebSocket.
+const WS_RECONNECT_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Connects to the Ethereum node over WebSocket (`ws://`, `wss://`) or HTTP (`http://`,
+/// `https://`).
+///
+/// Transient RPC errors are retried with backoff. A dropped WebSocket is reconnected (without a
+/// limit on the attempts) and its subscriptions resubscribed. Over HTTP, subscriptions are not supported and the event streams
+/// poll the node instead.
+pub async fn rpc_client(eth_node_address: &str) -> anyhow::Result<RpcClient> {
+    let builder = ClientBuilder::default().layer(RetryBackoffLayer::new(
+        MAX_RETRIES,
+        INITIAL_BACKOFF_MS,
+        COMPUTE_UNITS_PER_SECOND,
+    ));
+
+    let client = if eth_node_address.starts_with("ws://") || eth_node_address.starts_with("wss://")
+    {
+        let ws_connect = WsConnect::new(eth_node_address)
+            .with_max_retries(WS_MAX_RECONNECTS)
+            .with_retry_interval(WS_RECONNECT_INTERVAL);
+        builder.ws(ws_connect).await?
+    } else if eth_node_address.starts_with("http://") || eth_node_address.starts_with("https://") {
+        builder.http(eth_node_address.parse()?)
+    } else {
+        anyhow::bail!(
+            "Unsupported Ethereum node address {}, expected a ws(s):// or http(s):// URL",
+            eth_node_address
+        );
+    };
+
+    Ok(client)
+}
@@ -1,15 +1,19 @@
 use alloy::{
     hex::ToHexExt,
-    network::EthereumWallet,
     primitives::{Address, U256},
-    providers::{ProviderBuilder, WsConnect},
-    signers::local::LocalSigner,
+    providers::ProviderBuilder,
 };
 use ipfs_api_backend_hyper::{IpfsApi, IpfsClient};
-use std::{fs::File, str::FromStr};
+use std::fs::File;
 use tracing::info;
+use zkopml_contracts::ZkopmlClient;
 use zkopml_ml::{merkle::ModelMerkleTree, onnx::load_onnx_model};
 
+use crate::{
+    manifest::{DeploymentManifest, contract_address},
+    provider, signer,
+};
+
 #[derive(clap::Args, Debug, Clone)]
 pub struct RegisterArgs {
     #[arg(long, short, help = "Verbosity level (0-4)", action = clap::ArgAction::Count)]
@@ -19,11 +23,16 @@ pub struct RegisterArgs {
     #[clap(long)]
     pub eth_node_address: String,
 
-    /// Address of the ModelRegistry contract
+    /// Path of the deployment manifest written by `deploy`, instead of the contract addresses
     #[clap(long)]
-    pub model_registry_address: Address,
+    pub deployment: Option<String>,
 
-    /// Secret key to use for registering the model
+    /// Address of the ModelRegistry contract
+    #[clap(long, required_unless_present = "deployment")]
+    pub model_registry_address: Option<Address>,
+
+    /// Signer to use for registering the model: hex private key, keystore:<path>, mnemonic:<path>
+    /// or remote:<url>
     #[clap(long)]
     pub user_key: String,
 
@@ -35,15 +44,24 @@ pub struct RegisterArgs {
 pub async fn register(args: RegisterArgs) -> anyhow::Result<()> {
     // Initialize the user wallet
     info!("Initializing user wallet.");
-    let user_signer = LocalSigner::from_str(&args.user_key)?;
-    let user_wallet = EthereumWallet::from(user_signer);
-    let ws_connect = WsConnect::new(args.eth_node_address);
+    let user_wallet = signer::wallet(&args.user_key).await?;
     let user_provider = ProviderBuilder::new()
         .wallet(&user_wallet)
-        .connect_ws(ws_connect)
-        .await?;
+        .connect_client(provider::rpc_client(&args.eth_node_address).await?);
     info!("User address: {}", user_wallet.default_signer().address());
 
+    // Read the contract addresses from the deployment manifest, if any
+    let deployment = DeploymentManifest::load_optional(args.deployment.as_ref())?;
+    if let Some(deployment) = deployment.as_ref() {
+        deployment.check_chain_id(&user_provider).await?;
+    }
+    let model_registry_address = contract_address(
+        args.model_registry_address,
+        deployment.as_ref(),
+        |deployment| deployment.model_registry,
+        "model-registry-address",
+    )?;
+
     // Read the model file
     info!("Reading the model file from {}", args.model_path);
     let model_path = args.model_path.clone();
@@ -58,28 +76,24 @@ pub async fn register(args: RegisterArgs) -> anyhow::Result<()> {
 
     // Publish the model to the decentralized storage (IPFS)
     info!("Publishing the model to the decentralized storage (IPFS).");
-    let client = IpfsClient::default();
+    let ipfs_client = IpfsClient::default();
     let file = File::open(model_path)?;
-    let result = client.add(file).await?;
+    let result = ipfs_client.add(file).await?;
     info!("Model published to IPFS with hash: {}", result.hash);
 
     // Publish the model metadata to the ModelRegistry contract
     info!("Publishing the model metadata to the ModelRegistry contract.");
-    let model_registry =
-        zkopml_contracts::ModelRegistry::new(args.model_registry_address, user_provider.clone());
-    let tx = model_registry
-        .registerModel(
+    let client = ZkopmlClient::new(user_provider, model_registry_address);
+    let model_id = client
+        .register_model(
             format!("ipfs://{}", result.hash),
             merkle_tree.root().into(),
-            U256::from(nodes_len).into(),
+            U256::from(nodes_len),
         )
-        .send()
         .await?;
-    info!("Transaction hash: {}", tx.tx_hash());
-    std::thread::sleep(std::time::Duration::from_secs(10));
-    let model_id = model_registry.modelCounter().call().await? - U256::from(1);
     info!("Model registered with ID: {}", model_id);
 
+    let model_registry = client.model_registry();
     let model = model_registry.getModel(model_id).call().await?;
     info!("Model info:");
     info!("  ID: {}", model.id);
@@ -0,0 +1,183 @@
+use serde::Serialize;
+use std::{collections::BTreeMap, path::Path};
+use tracing::info;
+
+/// Cycles and timings of a single ONNX operator, as executed (and proven) by `prove`.
+#[derive(Serialize, Debug, Clone)]
+pub struct OperatorReport {
+    pub operator_index: usize,
+    pub name: String,
+    pub op_type: String,
+    /// Shapes of the operator input tensors (including graph initializers).
+    pub input_shapes: Vec<Vec<usize>>,
+    /// Total number of elements of the operator input tensors.
+    pub input_elements: usize,
+    pub cycles: u64,
+    /// Cycles of the guest sections (`cycle-tracker` markers of the SP1 program).
+    pub sections: BTreeMap<String, u64>,
+    /// Host time of the SP1 program execution.
+    pub execution_secs: f64,
+    /// Time of the proof generation, if the operator was proven.
+    pub proving_secs: Option<f64>,
+}
+
+/// Summary statistics of a `prove` run, matching the columns of the results table.
+#[derive(Serialize, Debug, Clone)]
+pub struct ReportSummary {
+    /// Number of ONNX operators of the model.
+    pub num_operators: usize,
+    /// Number of reported operators per op type.
+    pub op_types: BTreeMap<String, usize>,
+    pub total_cycles: u64,
+    /// Index of the reported operator with the most cycles.
+    pub most_expensive_operator: usize,
+    pub most_expensive_op_type: String,
+    pub most_expensive_cycles: u64,
+    pub challenge_window_secs: u64,
+    pub response_window_secs: u64,
+    /// Time of the bisection game: 2 × ceil(log₂(number of ONNX operators)) × response window.
+    pub bisection_secs: u64,
+    /// Proving time of the most expensive operator, if proven.
+    pub proving_secs: Option<f64>,
+    /// zk-OPML time: challenge window + bisection + proving of the most expensive operator.
+    pub zkopml_secs: Option<f64>,
+}
+
+/// Per-operator report of a `prove` run, saved as JSON or CSV.
+#[derive(Serialize, Debug, Clone)]
+pub struct ProveReport {
+    pub operators: Vec<OperatorReport>,
+    pub summary: ReportSummary,
+}
+
+impl ProveReport {
+    pub fn new(
+        operators: Vec<OperatorReport>,
+        num_operators: usize,
+        challenge_window_secs: u64,
+        response_window_secs: u64,
+    ) -> anyhow::Result<Self> {
+        let most_expensive = operators
+            .iter()
+            .max_by_key(|operator| operator.cycles)
+            .ok_or_else(|| anyhow::anyhow!("No operator to report"))?;
+
+        let mut op_types = BTreeMap::new();
+        for operator in operators.iter() {
+            *op_types.entry(operator.op_type.clone()).or_insert(0) += 1;
+        }
+
+        let bisection_secs =
+            2 * num_operators.next_power_of_two().trailing_zeros() as u64 * response_window_secs;
+        let proving_secs = most_expensive.proving_secs;
+
+        let summary = ReportSummary {
+            num_operators,
+            op_types,
+            total_cycles: operators.iter().map(|operator| operator.cycles).sum(),
+            most_expensive_operator: most_expensive.operator_index,
+            most_expensive_op_type: most_expensive.op_type.clone(),
+            most_expensive_cycles: most_expensive.cycles,
+            challenge_window_secs,
+            response_window_secs,
+            bisection_secs,
+            proving_secs,
+            zkopml_secs: proving_secs
+                .map(|proving_secs| (challenge_window_secs + bisection_secs) as f64 + proving_secs),
+        };
+
+        Ok(Self { operators, summary })
+    }
+
+    pub fn log_summary(&self) {
+        let summary = &self.summary;
+        let op_types: Vec<String> = summary
+            .op_types
+            .iter()
+            .map(|(op_type, count)| format!("{} x {}", count, op_type))
+            .collect();
+        info!("Operators: {}", op_types.join(", "));
+        info!("Total cycles: {}", summary.total_cycles);
+        info!(
+            "Most expensive operator: {} ({}) with {} cycles",
+            summary.most_expensive_operator,
+            summary.most_expensive_op_type,
+            summary.most_expensive_cycles
+        );
+        match summary.zkopml_secs {
+            Some(zkopml_secs) => info!(
+                "zk-OPML time: {} s + {} s + {:.0} s = {:.0} s",
+                summary.challenge_window_secs,
+                summary.bisection_secs,
+                summary.proving_secs.unwrap_or_default(),
+                zkopml_secs
+            ),
+            None => info!(
+                "zk-OPML time without proving: {} s + {} s",
+                summary.challenge_window_secs, summary.bisection_secs
+            ),
+        }
+    }
+
+    /// Saves the report as CSV (one row per operator) if the path ends with `.csv`, as JSON
+    /// (operators and summary) otherwise.
+    pub fn save(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        let content = if path.extension().is_some_and(|extension| extension == "csv") {
+            self.to_csv()
+        } else {
+            serde_json::to_string_pretty(self)?
+        };
+        std::fs::write(path, content)?;
+        info!("Saved report to {}", path.display());
+
+        Ok(())
+    }
+
+    fn to_csv(&self) -> String {
+        let mut csv = String::from(
+            "operator_index,name,op_type,input_shapes,input_elements,cycles,sections,execution_secs,proving_secs\n",
+        );
+        for operator in self.operators.iter() {
+            let input_shapes: Vec<String> = operator
+                .input_shapes
+                .iter()
+                .map(|shape| {
+                    let dims: Vec<String> = shape.iter().map(|dim| dim.to_string()).collect();
+                    dims.join("x")
+                })
+                .collect();
+            let sections: Vec<String> = operator
+                .sections
+                .iter()
+                .map(|(section, cycles)| format!("{}={}", section, cycles))
+                .collect();
+            let row = [
+                operator.operator_index.to_string(),
+                csv_field(&operator.name),
+                csv_field(&operator.op_type),
+                csv_field(&input_shapes.join(";")),
+                operator.input_elements.to_string(),
+                operator.cycles.to_string(),
+                csv_field(&sections.join(";")),
+                operator.execution_secs.to_string(),
+                operator
+                    .proving_secs
+                    .map(|secs| secs.to_string())
+                    .unwrap_or_default(),
+            ];
+            csv.push_str(&row.join(","));
+            csv.push('\n');
+        }
+
+        csv
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -1,17 +1,20 @@
 use alloy::{
-    network::EthereumWallet,
-    primitives::{Address, Bytes, U256},
-    providers::{ProviderBuilder, WsConnect},
-    signers::local::LocalSigner,
+    primitives::{Address, Bytes, U256, utils::format_ether},
+    providers::ProviderBuilder,
 };
 use candle_core::Tensor;
 use candle_onnx::eval::get_tensor;
-use std::{collections::HashMap, str::FromStr};
+use std::collections::HashMap;
 use tracing::info;
+use zkopml_contracts::{ZkopmlClient, client::DEFAULT_POLL_INTERVAL};
 use zkopml_ml::{
-    data::{extract_input_data, tensor_hash},
+    data::{extract_input_data, state_hash, tensor_hash},
     onnx::load_onnx_model,
-    utils::hash_buffer,
+};
+
+use crate::{
+    manifest::{DeploymentManifest, contract_address},
+    provider, signer,
 };
 
 #[derive(clap::Args, Debug, Clone)]
@@ -23,9 +26,13 @@ pub struct RequestArgs {
     #[clap(long)]
     pub eth_node_address: String,
 
-    /// Address of the ModelRegistry contract
+    /// Path of the deployment manifest written by `deploy`, instead of the contract addresses
     #[clap(long)]
-    pub model_registry_address: Address,
+    pub deployment: Option<String>,
+
+    /// Address of the ModelRegistry contract
+    #[clap(long, required_unless_present = "deployment")]
+    pub model_registry_address: Option<Address>,
 
     /// Path to the model file (ONNX)
     #[clap(long)]
@@ -35,27 +42,51 @@ pub struct RequestArgs {
     #[clap(long)]
     pub input_data_path: String,
 
-    /// Secret key to use for requesting the inference
+    /// Signer to use for requesting the inference: hex private key, keystore:<path>, mnemonic:<path>
+    /// or remote:<url>
     #[clap(long)]
     pub user_key: String,
 
     /// Model id to use
     #[clap(long)]
     pub model_id: u8,
+
+    /// Request a ZK proof of the whole inference instead of optimistic verification
+    /// The result is final as soon as the inference is responded
+    #[clap(long)]
+    pub zk: bool,
+
+    /// Fee (in wei) escrowed with the request, paid to the responder after the challenge window
+    /// (immediately for full-ZK inferences) or to a successful challenger
+    #[clap(long, default_value = "0")]
+    pub fee: U256,
+
+    /// Wait for the inference to be responded and output the result
+    #[clap(long)]
+    pub wait: bool,
 }
 
 pub async fn request(args: RequestArgs) -> anyhow::Result<()> {
     // Initialize the user wallet
     info!("Initializing user wallet.");
-    let user_signer = LocalSigner::from_str(&args.user_key)?;
-    let user_wallet = EthereumWallet::from(user_signer);
-    let ws_connect = WsConnect::new(args.eth_node_address);
+    let user_wallet = signer::wallet(&args.user_key).await?;
     let user_provider = ProviderBuilder::new()
         .wallet(&user_wallet)
-        .connect_ws(ws_connect)
-        .await?;
+        .connect_client(provider::rpc_client(&args.eth_node_address).await?);
     info!("User address: {}", user_wallet.default_signer().address());
 
+    // Read the contract addresses from the deployment manifest, if any
+    let deployment = DeploymentManifest::load_optional(args.deployment.as_ref())?;
+    if let Some(deployment) = deployment.as_ref() {
+        deployment.check_chain_id(&user_provider).await?;
+    }
+    let model_registry_address = contract_address(
+        args.model_registry_address,
+        deployment.as_ref(),
+        |deployment| deployment.model_registry,
+        "model-registry-address",
+    )?;
+
     // Read the model just to structure the input data
     // TODO: read the model file from IPFS based on model id
     // For now, we are going to assume there is only one model
@@ -74,27 +105,35 @@ pub async fn request(args: RequestArgs) -> anyhow::Result<()> {
         let hash = tensor_hash(tensor);
         input_hashes.insert(name.clone(), hash);
     }
-    let mut input_entries = input_hashes.iter().collect::<Vec<_>>();
-    input_entries.sort_by(|a, b| a.0.cmp(b.0));
-    let hash = hash_buffer(serde_json::to_string(&input_entries).unwrap().as_bytes());
+    let hash = state_hash(&input_hashes);
     inputs.retain(|k: &String, _| input_names.contains(k));
 
     // Request the inference
-    let model_registry =
-        zkopml_contracts::ModelRegistry::new(args.model_registry_address, user_provider);
+    let client = ZkopmlClient::new(user_provider, model_registry_address);
     let model_id = U256::from(args.model_id);
     let input_data = Bytes::copy_from_slice(serde_json::to_string(&inputs).unwrap().as_bytes());
 
-    let tx = model_registry
-        .requestInference(model_id, input_data, hash.into())
-        .send()
+    let inference_id = client
+        .request_inference(model_id, input_data, hash.into(), args.zk, args.fee)
         .await?;
-    info!("Transaction hash: {}", tx.tx_hash());
-    std::thread::sleep(std::time::Duration::from_secs(10));
-    let inference_id = model_registry.inferenceCounter().call().await? - U256::from(1);
-    info!("Inference request sent with id: {}", inference_id);
+    info!(
+        "Inference request sent with id: {} (fee: {} ETH)",
+        inference_id,
+        format_ether(args.fee)
+    );
 
-    // TODO: wait and listen for result
+    if args.wait {
+        info!("Waiting for the inference result.");
+        let inference = client
+            .wait_for_result(inference_id, DEFAULT_POLL_INTERVAL)
+            .await?;
+        info!(
+            "Inference {} responded by {}: {}",
+            inference_id,
+            inference.responder,
+            String::from_utf8_lossy(&inference.outputData)
+        );
+    }
 
     Ok(())
 }
@@ -0,0 +1,113 @@
+use alloy::{
+    hex::ToHexExt,
+    primitives::{Address, Bytes, U256},
+    providers::{Provider, ProviderBuilder},
+};
+use tracing::info;
+use zkopml_ml::public_values::{PROOF_DOMAIN_CHALLENGE, PublicValues};
+
+use crate::{
+    artifact::{Program, ProofArtifact},
+    manifest::{DeploymentManifest, contract_address},
+    provider, signer,
+    vkey::check_program_vkey,
+};
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct ResolveArgs {
+    #[arg(long, short, help = "Verbosity level (0-4)", action = clap::ArgAction::Count)]
+    pub v: u8,
+
+    /// Address of the Ethereum node endpoint to use
+    #[clap(long)]
+    pub eth_node_address: String,
+
+    /// Path of the deployment manifest written by `deploy`, instead of the contract addresses
+    #[clap(long)]
+    pub deployment: Option<String>,
+
+    /// Address of the FaultProof contract
+    #[clap(long, required_unless_present = "deployment")]
+    pub fault_proof_address: Option<Address>,
+
+    /// Signer to use for resolving the challenge: hex private key, keystore:<path>, mnemonic:<path>
+    /// or remote:<url>
+    #[clap(long)]
+    pub user_key: String,
+
+    /// Directory of the saved operator proof (written by `prove --output-dir` or `verify`)
+    #[clap(long)]
+    pub proof_dir: String,
+}
+
+pub async fn resolve(args: ResolveArgs) -> anyhow::Result<()> {
+    // Initialize the user wallet
+    let user_wallet = signer::wallet(&args.user_key).await?;
+    let user_provider = ProviderBuilder::new()
+        .wallet(&user_wallet)
+        .connect_client(provider::rpc_client(&args.eth_node_address).await?);
+    info!("User address: {}", user_wallet.default_signer().address());
+
+    // Read the contract addresses from the deployment manifest, if any
+    let deployment = DeploymentManifest::load_optional(args.deployment.as_ref())?;
+    if let Some(deployment) = deployment.as_ref() {
+        deployment.check_chain_id(&user_provider).await?;
+    }
+    let fault_proof_address = contract_address(
+        args.fault_proof_address,
+        deployment.as_ref(),
+        |deployment| deployment.fault_proof,
+        "fault-proof-address",
+    )?;
+
+    info!("Reading the proof from {}", args.proof_dir);
+    let artifact = ProofArtifact::load(&args.proof_dir)?;
+    anyhow::ensure!(
+        artifact.metadata.program == Program::Operator,
+        "Challenges are resolved with operator proofs, got a {:?} proof",
+        artifact.metadata.program
+    );
+
+    // The challenge is the one the proof is bound to
+    let public_values = PublicValues::decode(artifact.proof.public_values.as_slice())?;
+    let chain_id = user_provider.get_chain_id().await?;
+    anyhow::ensure!(
+        public_values.context.chainId == U256::from(chain_id),
+        "The proof is bound to chain id {}, the node is on chain id {}",
+        public_values.context.chainId,
+        chain_id
+    );
+    anyhow::ensure!(
+        public_values.context.verifier == fault_proof_address,
+        "The proof is bound to FaultProof contract {}",
+        public_values.context.verifier
+    );
+    anyhow::ensure!(
+        public_values.context.domain == PROOF_DOMAIN_CHALLENGE,
+        "The proof is bound to a full-ZK inference, not to a challenge"
+    );
+    let challenge_id = public_values.context.id;
+
+    // The proof must be of the program the FaultProof contract verifies
+    check_program_vkey(&user_provider, fault_proof_address, &artifact.vk).await?;
+
+    let fault_proof = zkopml_contracts::FaultProof::new(fault_proof_address, user_provider.clone());
+    let proof_bytes = artifact.proof.bytes();
+    info!(
+        "Resolving the challenge id {} for operator {} with SP1 proof verification (proof: {})",
+        challenge_id,
+        public_values.operatorIndex,
+        proof_bytes.encode_hex()
+    );
+    let tx = fault_proof
+        .resolveOpenChallenge(
+            challenge_id,
+            Bytes::copy_from_slice(artifact.proof.public_values.as_slice()),
+            Bytes::copy_from_slice(&proof_bytes),
+        )
+        .send()
+        .await?;
+    info!("Transaction hash: {}", tx.tx_hash());
+
+    Ok(())
+}
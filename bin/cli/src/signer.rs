@@ -0,0 +1,244 @@
+use alloy::{
+    consensus::{SignableTransaction, Transaction, TxEnvelope},
+    eips::eip2718::Decodable2718,
+    network::{EthereumWallet, TxSigner},
+    primitives::{Address, Bytes, Signature},
+    rpc::{
+        client::RpcClient,
+        types::{TransactionInput, TransactionRequest},
+    },
+    signers::local::{LocalSigner, MnemonicBuilder, PrivateKeySigner, coins_bip39::English},
+};
+use anyhow::Context;
+use std::{collections::HashMap, str::FromStr};
+
+/// Help of the signer options (`--user-key`, `--deployer-key`, `--owner-key`).
+pub const SIGNER_HELP: &str = "hex private key, keystore:<path>[?password-file=<path>], \
+    mnemonic:<path>[?index=<n>|path=<derivation path>] or remote:<url>[#<address>]";
+
+/// Signer of the transactions of a command, given as a hex private key or as
+/// `<kind>:<location>[?<option>=<value>&...]`:
+///
+/// - `keystore:<path>`: encrypted JSON keystore, the password is read from `password-file` or
+///   prompted for.
+/// - `mnemonic:<path>`: BIP-39 mnemonic read from a file, derived at `path` (defaults to the
+///   first Ethereum account, `m/44'/60'/0'/0/<index>`).
+/// - `remote:<url>[#<address>]`: remote signer over JSON-RPC (`eth_accounts` and
+///   `eth_signTransaction`, as served by Web3Signer or a node with unlocked accounts), using
+///   its first account unless one is given.
+///
+/// Keys are never part of the spec besides the legacy hex private key, so they do not end up in
+/// the shell history or the process list.
+pub enum SignerSpec {
+    PrivateKey(String),
+    Keystore {
+        path: String,
+        password_file: Option<String>,
+    },
+    Mnemonic {
+        path: String,
+        derivation_path: Option<String>,
+        index: Option<u32>,
+    },
+    Remote {
+        url: String,
+        address: Option<Address>,
+    },
+}
+
+impl FromStr for SignerSpec {
+    type Err = anyhow::Error;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        anyhow::ensure!(
+            !spec.is_empty(),
+            "Missing signer (e.g. an empty `*_SIGNER` variable of the justfile)"
+        );
+        let Some((kind, location)) = spec.split_once(':') else {
+            return Ok(Self::PrivateKey(spec.to_string()));
+        };
+        let (location, mut options) = match location.split_once('?') {
+            Some((location, query)) => (location, parse_options(query)?),
+            None => (location, HashMap::new()),
+        };
+
+        let signer = match kind {
+            "keystore" => Self::Keystore {
+                path: location.to_string(),
+                password_file: options.remove("password-file"),
+            },
+            "mnemonic" => Self::Mnemonic {
+                path: location.to_string(),
+                derivation_path: options.remove("path"),
+                index: options
+                    .remove("index")
+                    .map(|index| index.parse())
+                    .transpose()?,
+            },
+            "remote" => {
+                let (url, address) = match location.split_once('#') {
+                    Some((url, address)) => (url, Some(address.parse()?)),
+                    None => (location, None),
+                };
+                Self::Remote {
+                    url: url.to_string(),
+                    address,
+                }
+            }
+            _ => anyhow::bail!("Unknown signer kind `{}`, expected a {}", kind, SIGNER_HELP),
+        };
+        anyhow::ensure!(
+            options.is_empty(),
+            "Unknown signer options: {}",
+            options.into_keys().collect::<Vec<_>>().join(", ")
+        );
+
+        Ok(signer)
+    }
+}
+
+impl SignerSpec {
+    /// Builds the wallet signing the transactions of the command.
+    pub async fn wallet(&self) -> anyhow::Result<EthereumWallet> {
+        let wallet = match self {
+            Self::PrivateKey(key) => EthereumWallet::from(PrivateKeySigner::from_str(key)?),
+            Self::Keystore {
+                path,
+                password_file,
+            } => {
+                let password = match password_file {
+                    Some(password_file) => std::fs::read_to_string(password_file)
+                        .with_context(|| format!("Failed to read password file {password_file}"))?
+                        .trim_end_matches(['\r', '\n'])
+                        .to_string(),
+                    None => rpassword::prompt_password(format!("Password of keystore {path}: "))?,
+                };
+                let signer = LocalSigner::decrypt_keystore(path, password)
+                    .with_context(|| format!("Failed to decrypt keystore {path}"))?;
+                EthereumWallet::from(signer)
+            }
+            Self::Mnemonic {
+                path,
+                derivation_path,
+                index,
+            } => {
+                let phrase = std::fs::read_to_string(path)
+                    .with_context(|| format!("Failed to read mnemonic file {path}"))?;
+                let mut builder = MnemonicBuilder::<English>::default().phrase(phrase.trim());
+                if let Some(derivation_path) = derivation_path {
+                    builder = builder.derivation_path(derivation_path)?;
+                }
+                if let Some(index) = index {
+                    builder = builder.index(*index)?;
+                }
+                EthereumWallet::from(builder.build()?)
+            }
+            Self::Remote { url, address } => {
+                EthereumWallet::new(RemoteSigner::connect(url, *address).await?)
+            }
+        };
+
+        Ok(wallet)
+    }
+}
+
+/// Builds the wallet of a signer option, see [`SignerSpec`].
+pub async fn wallet(spec: &str) -> anyhow::Result<EthereumWallet> {
+    spec.parse::<SignerSpec>()?.wallet().await
+}
+
+fn parse_options(query: &str) -> anyhow::Result<HashMap<String, String>> {
+    query
+        .split('&')
+        .map(|option| {
+            option
+                .split_once('=')
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .ok_or_else(|| anyhow::anyhow!("Invalid signer option `{}`", option))
+        })
+        .collect()
+}
+
+/// Signer delegating the signatures to a remote JSON-RPC endpoint with `eth_signTransaction`.
+#[derive(Debug, Clone)]
+pub struct RemoteSigner {
+    client: RpcClient,
+    address: Address,
+}
+
+impl RemoteSigner {
+    /// Connects to the remote signer and checks that it holds the account.
+    pub async fn connect(url: &str, address: Option<Address>) -> anyhow::Result<Self> {
+        let client = RpcClient::new_http(url.parse()?);
+        let accounts: Vec<Address> = client.request_noparams("eth_accounts").await?;
+        let address = match address {
+            Some(address) => {
+                anyhow::ensure!(
+                    accounts.contains(&address),
+                    "The remote signer {} does not hold account {}",
+                    url,
+                    address
+                );
+                address
+            }
+            None => *accounts
+                .first()
+                .with_context(|| format!("The remote signer {url} has no accounts"))?,
+        };
+
+        Ok(Self { client, address })
+    }
+}
+
+#[async_trait::async_trait]
+impl TxSigner<Signature> for RemoteSigner {
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    async fn sign_transaction(
+        &self,
+        tx: &mut dyn SignableTransaction<Signature>,
+    ) -> alloy::signers::Result<Signature> {
+        let mut request = TransactionRequest {
+            from: Some(self.address),
+            to: Some(tx.kind()),
+            gas: Some(tx.gas_limit()),
+            value: Some(tx.value()),
+            input: TransactionInput::new(tx.input().clone()),
+            nonce: Some(tx.nonce()),
+            chain_id: tx.chain_id(),
+            access_list: tx.access_list().cloned(),
+            transaction_type: Some(tx.ty()),
+            ..Default::default()
+        };
+        match tx.gas_price() {
+            Some(gas_price) => request.gas_price = Some(gas_price),
+            None => {
+                request.max_fee_per_gas = Some(tx.max_fee_per_gas());
+                request.max_priority_fee_per_gas = tx.max_priority_fee_per_gas();
+            }
+        }
+
+        let raw: Bytes = self
+            .client
+            .request("eth_signTransaction", (request,))
+            .await
+            .map_err(alloy::signers::Error::other)?;
+        let envelope =
+            TxEnvelope::decode_2718(&mut raw.as_ref()).map_err(alloy::signers::Error::other)?;
+        let signature = *envelope.signature();
+
+        // The remote signer must have signed this very transaction
+        let signer = signature
+            .recover_address_from_prehash(&tx.signature_hash())
+            .map_err(alloy::signers::Error::other)?;
+        if signer != self.address {
+            return Err(alloy::signers::Error::other(
+                "The remote signer signed another transaction",
+            ));
+        }
+
+        Ok(signature)
+    }
+}
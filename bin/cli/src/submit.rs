@@ -1,20 +1,37 @@
 use alloy::{
-    eips::BlockNumberOrTag,
-    network::EthereumWallet,
-    primitives::{Address, Bytes, U256},
-    providers::{Provider, ProviderBuilder, WsConnect},
-    rpc::types::Filter,
-    signers::local::LocalSigner,
-    sol,
-    sol_types::SolEvent,
+    primitives::{Address, Bytes, U256, utils::format_ether},
+    providers::{Provider, ProviderBuilder},
 };
+use anyhow::Context;
 use candle_core::{DType, Tensor};
 use candle_onnx::eval::{get_tensor, simple_eval_one};
-use futures_util::stream::StreamExt;
 use rand::Rng;
-use std::{collections::HashMap, str::FromStr};
-use tracing::info;
-use zkopml_ml::{data::tensor_hash, onnx::load_onnx_model, utils::hash_buffer};
+use sp1_sdk::include_elf;
+use std::collections::HashMap;
+use tracing::{error, info, warn};
+use zkopml_contracts::{
+    CHALLENGE_ACTOR_CHALLENGER, EventLog, EventStreamBuilder,
+    FaultProof::FaultProofEvents,
+    ModelRegistry::{self, ModelRegistryEvents},
+    ZkopmlClient, ZkopmlEvent,
+};
+use zkopml_ml::{
+    data::{state_hash, tensor_hash},
+    interface::GraphInterface,
+    merkle::ModelMerkleTree,
+    onnx::{Model, load_onnx_model},
+    public_values::ProofContext,
+};
+
+use crate::{
+    bond::ensure_bond,
+    keys::KeyCache,
+    manifest::{DeploymentManifest, contract_address},
+    proof_system::{ProofSystem, SP1Client, SP1Prover, Strategy, check_verifier},
+    prove::prove_aggregate,
+    provider, signer,
+    vkey::check_aggregate_program_vkey,
+};
 
 #[derive(clap::Args, Debug, Clone)]
 pub struct SubmitArgs {
@@ -25,15 +42,20 @@ pub struct SubmitArgs {
     #[clap(long)]
     pub eth_node_address: String,
 
-    /// Address of the ModelRegistry contract
+    /// Path of the deployment manifest written by `deploy`, instead of the contract addresses
     #[clap(long)]
-    pub model_registry_address: Address,
+    pub deployment: Option<String>,
+
+    /// Address of the ModelRegistry contract
+    #[clap(long, required_unless_present = "deployment")]
+    pub model_registry_address: Option<Address>,
 
     /// Address of the FaultProof contract
-    #[clap(long)]
-    pub fault_proof_address: Address,
+    #[clap(long, required_unless_present = "deployment")]
+    pub fault_proof_address: Option<Address>,
 
-    /// Secret key to use for requesting the inference
+    /// Signer to use for responding to the inferences: hex private key, keystore:<path>, mnemonic:<path>
+    /// or remote:<url>
     #[clap(long)]
     pub user_key: String,
 
@@ -52,292 +74,535 @@ pub struct SubmitArgs {
     /// Whether to submit wrong result (for testing purposes)
     #[clap(long, short)]
     pub defect: bool,
+
+    /// Type of SP1 prover of the proofs of full-ZK inferences (aggregation does not support the
+    /// mock prover)
+    #[clap(long, default_value = "network")]
+    pub sp1_prover: SP1Prover,
+
+    /// SP1 proof system of the proofs of full-ZK inferences
+    #[clap(long, default_value = "plonk")]
+    pub proof_system: ProofSystem,
+
+    /// Fulfillment strategy of the SP1 prover network
+    #[clap(long, default_value = "hosted")]
+    pub strategy: Strategy,
+
+    /// Directory of the SP1 proving/verifying key cache
+    #[clap(long, default_value = ".zkopml/keys")]
+    pub key_cache_dir: String,
+
+    /// Block to backfill the inference requests from (defaults to the deploy block of the
+    /// deployment, otherwise to the next block)
+    #[clap(long)]
+    pub from_block: Option<u64>,
+
+    /// Number of confirmations of the blocks of the events before handling them
+    #[clap(long, default_value_t = 0)]
+    pub confirmations: u64,
+
+    /// Minimum fee (in wei) escrowed by a request to respond to it
+    #[clap(long, default_value = "0")]
+    pub min_fee: U256,
 }
 
-sol!(
-    #[derive(Debug)]
-    event InferenceRequested(uint256 modelId, uint256 inferenceId, address requester, bytes inputData, bytes32 inputDataHash);
-    #[derive(Debug)]
-    event ChallengeCreated(uint256 challengeId, uint256 inferenceId, address responder, address challenger);
-    #[derive(Debug)]
-    event OperatorExecutionProposed(
-        uint256 challengeId, uint256 operatorPosition, bytes32 inputDataHash, bytes32 outputDataHash
-    );
-    #[derive(Debug)]
-    event OperatorExecutionResponded(uint256 challengeId, uint256 operatorPosition, bool input, bool output);
-    #[derive(Debug)]
-    event ChallengeResolved(uint256 challengeId, bool success, address winner);
-);
+const ELF: &[u8] = include_elf!("zkopml-zk");
+const AGGREGATE_ELF: &[u8] = include_elf!("zkopml-zk-aggregate");
 
 pub async fn submit(args: SubmitArgs) -> anyhow::Result<()> {
     // Initialize the user wallet
-    let user_signer = LocalSigner::from_str(&args.user_key)?;
-    let user_wallet = EthereumWallet::from(user_signer);
-    let ws_connect = WsConnect::new(args.eth_node_address);
+    let user_wallet = signer::wallet(&args.user_key).await?;
     let user_provider = ProviderBuilder::new()
         .wallet(&user_wallet)
-        .connect_ws(ws_connect)
-        .await?;
+        .connect_client(provider::rpc_client(&args.eth_node_address).await?);
     info!("User address: {}", user_wallet.default_signer().address());
 
-    // Listen for inference requests
-    let _model_registry =
-        zkopml_contracts::ModelRegistry::new(args.model_registry_address, user_provider.clone());
-    let inference_request_filter = Filter::new()
-        .address(args.model_registry_address)
-        .event("InferenceRequested(uint256,uint256,address,bytes,bytes32)")
-        .from_block(BlockNumberOrTag::Latest);
-    let sub = user_provider
-        .subscribe_logs(&inference_request_filter)
-        .await?;
-    let mut stream = sub.into_stream();
-
-    while let Some(log) = stream.next().await {
-        // Parse the data
-        info!("Received inference request: {:?}", log);
-        let request = InferenceRequested::decode_log_data(log.data());
-        if request.is_err() {
-            info!("Failed to decode the request data");
-            continue;
+    // Read the contract addresses from the deployment manifest, if any
+    let deployment = DeploymentManifest::load_optional(args.deployment.as_ref())?;
+    if let Some(deployment) = deployment.as_ref() {
+        deployment.check_chain_id(&user_provider).await?;
+    }
+    let model_registry_address = contract_address(
+        args.model_registry_address,
+        deployment.as_ref(),
+        |deployment| deployment.model_registry,
+        "model-registry-address",
+    )?;
+    let fault_proof_address = contract_address(
+        args.fault_proof_address,
+        deployment.as_ref(),
+        |deployment| deployment.fault_proof,
+        "fault-proof-address",
+    )?;
+
+    let zkopml_client = ZkopmlClient::new(user_provider.clone(), model_registry_address)
+        .with_fault_proof(fault_proof_address);
+    // Optimistic responses lock a response bond, refuse to start without one
+    let user_address = user_wallet.default_signer().address();
+    let response_bond = zkopml_client.response_bond().await?;
+    ensure_bond(&zkopml_client, user_address, response_bond, "responding").await?;
+
+    // Listen for inference requests and for the challenges of the responses
+    // Backfill the events since the deployment, unless another block is given
+    let deploy_block = deployment
+        .as_ref()
+        .map(|deployment| deployment.deploy_block);
+    let from_block = args.from_block.or(deploy_block);
+    let mut events = EventStreamBuilder::new(
+        user_provider.clone(),
+        vec![model_registry_address, fault_proof_address],
+    )
+    .from_block(from_block)
+    .confirmations(args.confirmations)
+    .start();
+
+    let mut submitter = Submitter {
+        args,
+        zkopml_client,
+        user_address,
+        response_bond,
+        inference_hashes: HashMap::new(),
+        challenges: HashMap::new(),
+    };
+    while let Some(event) = events.next().await {
+        let event = event?;
+        let (block_number, transaction_hash) = (event.block_number, event.transaction_hash);
+        // A failing event (e.g. a request with malformed input data) must not stop the submitter
+        if let Err(error) = submitter.handle(event).await {
+            warn!(
+                "Failed to handle the event of transaction {} in block {}: {:#}",
+                transaction_hash, block_number, error
+            );
         }
-        let request = request.unwrap();
-        info!("Decoded request: {:?}", request);
-        let model_id: U256 = request.modelId;
-        let inference_id: U256 = request.inferenceId;
-        let input_data = request.inputData;
-        info!(
-            "Model id: {}, Inference id: {}, Input data: {:?}",
-            model_id, inference_id, input_data
-        );
 
-        // Perform the inference
-        // TODO: read the model file from IPFS based on model id
-        // For now, we are going to assume there is only one model
-        info!("Reading the model file from {}", args.model_path);
-        let model_path = args.model_path.clone();
-        let model = load_onnx_model(&model_path)?;
+        // TODO: handle expired challenges
+    }
 
-        let mut inputs: HashMap<String, Tensor> = serde_json::from_slice(&input_data).unwrap();
+    // The event stream retries the failures of the node, it only ends if it was stopped
+    anyhow::bail!("The event stream ended")
+}
 
-        for (name, tensor) in &inputs {
-            match tensor.dtype() {
-                DType::F32 => {
-                    info!(
-                        "Input tensor '{}': {:?}",
-                        name,
-                        tensor.flatten_all()?.to_vec1::<f32>()?
-                    );
+/// State of the submitter, updated by the events of the contracts.
+struct Submitter<P> {
+    args: SubmitArgs,
+    zkopml_client: ZkopmlClient<P>,
+    user_address: Address,
+    response_bond: U256,
+    /// Operator hashes of the responded (challengeable) inferences, by inference id
+    inference_hashes: HashMap<U256, Vec<([u8; 32], [u8; 32])>>,
+    /// Open challenges of the responses (inference id by challenge id), each challenger bisects
+    /// the operators independently
+    challenges: HashMap<U256, U256>,
+}
+
+impl<P: Provider + Clone + 'static> Submitter<P> {
+    /// Responds to the inference requests and to the operator executions proposed by the
+    /// challengers of the responses.
+    async fn handle(&mut self, event: EventLog) -> anyhow::Result<()> {
+        match event.event {
+            ZkopmlEvent::ModelRegistry(ModelRegistryEvents::InferenceRequested(request)) => {
+                info!(
+                    "Received inference request in block {}: {:?}",
+                    event.block_number, request
+                );
+                let inference_id = request.inferenceId;
+                if let Some(hashes) = respond_to_request(
+                    &self.args,
+                    &self.zkopml_client,
+                    self.user_address,
+                    self.response_bond,
+                    request,
+                )
+                .await?
+                {
+                    self.inference_hashes.insert(inference_id, hashes);
                 }
-                DType::F64 => {
+            }
+            ZkopmlEvent::FaultProof(FaultProofEvents::ChallengeCreated(request)) => {
+                if request.responder != self.user_address
+                    || !self.inference_hashes.contains_key(&request.inferenceId)
+                {
+                    return Ok(());
+                }
+                info!(
+                    "Challenge id {} for inference id {} created by {}",
+                    request.challengeId, request.inferenceId, request.challenger
+                );
+                self.challenges
+                    .insert(request.challengeId, request.inferenceId);
+            }
+            ZkopmlEvent::FaultProof(FaultProofEvents::OperatorExecutionProposed(request)) => {
+                let Some(inference_id) = self.challenges.get(&request.challengeId) else {
+                    return Ok(());
+                };
+                // Backfilled proposals may have been responded already
+                let challenge = self.zkopml_client.challenge(request.challengeId).await?;
+                if challenge.resolved
+                    || u8::from(challenge.lastActor) != CHALLENGE_ACTOR_CHALLENGER
+                    || (challenge.operatorLow + challenge.operatorHigh) / U256::from(2)
+                        != request.operatorPosition
+                {
                     info!(
-                        "Input tensor '{}': {:?}",
-                        name,
-                        tensor.flatten_all()?.to_vec1::<f64>()?
+                        "Operator execution proposed for challenge id {} at position {} was already responded",
+                        request.challengeId, request.operatorPosition
                     );
+                    return Ok(());
                 }
-                _ => {}
+                tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+                let operator_position = request.operatorPosition;
+                info!(
+                    "Operator execution proposed for challenge id {} at position {}",
+                    request.challengeId, request.operatorPosition
+                );
+                let (input_data_hash, output_data_hash) = *self.inference_hashes[inference_id]
+                    .get(operator_position.to::<usize>())
+                    .context("Operator position out of the model")?;
+                let input_data_match = input_data_hash == request.inputDataHash;
+                let output_data_match = output_data_hash == request.outputDataHash;
+                info!(
+                    "Operator execution response for challenge id {} at position {}: {}, {}",
+                    request.challengeId,
+                    request.operatorPosition,
+                    input_data_match,
+                    output_data_match
+                );
+                let tx = self
+                    .zkopml_client
+                    .fault_proof()?
+                    .respondOperatorExecution(
+                        request.challengeId,
+                        input_data_match,
+                        output_data_match,
+                    )
+                    .send()
+                    .await?;
+                info!("Transaction hash: {}", tx.tx_hash());
+            }
+            ZkopmlEvent::FaultProof(FaultProofEvents::ChallengeResolved(request)) => {
+                if self.challenges.remove(&request.challengeId).is_none() {
+                    return Ok(());
+                }
+                info!(
+                    "Challenge id {} resolved, challenge actor winner: {}, address winner: {}",
+                    request.challengeId, request.success, request.winner
+                );
+            }
+            ZkopmlEvent::FaultProof(FaultProofEvents::InferenceFaulty(request)) => {
+                if self.inference_hashes.remove(&request.inferenceId).is_none() {
+                    return Ok(());
+                }
+                // The inference is settled, its other challenges are won by their challengers
+                info!(
+                    "Inference {} was proven faulty by challenge id {}",
+                    request.inferenceId, request.challengeId
+                );
+                self.challenges
+                    .retain(|_, inference_id| *inference_id != request.inferenceId);
             }
+            _ => {}
         }
 
-        let mut inference_hashes: HashMap<U256, Vec<([u8; 32], [u8; 32])>> = HashMap::new();
+        Ok(())
+    }
+}
 
-        // If the defect flag is set, randomly select an operator to produce a defect
-        let defect_index = if args.defect {
-            if let Some(operator_index) = args.operator_index {
-                Some(operator_index as usize)
-            } else {
-                let mut rng = rand::rng();
-                let random_index = rng.random_range(0..model.num_operators());
-                Some(random_index)
+/// Performs a requested inference and responds to it, returns the operator hashes of the response
+/// if it can be challenged (optimistic inference).
+///
+/// The operator hashes of the backfilled inferences already responded by the user are recomputed,
+/// so their challenges are still responded after a restart. Full-ZK inferences are proven and
+/// responded in a background task.
+async fn respond_to_request<P: Provider + Clone + 'static>(
+    args: &SubmitArgs,
+    zkopml_client: &ZkopmlClient<P>,
+    user_address: Address,
+    response_bond: U256,
+    request: ModelRegistry::InferenceRequested,
+) -> anyhow::Result<Option<Vec<([u8; 32], [u8; 32])>>> {
+    let model_id: U256 = request.modelId;
+    let inference_id: U256 = request.inferenceId;
+
+    // Backfilled requests may have been responded already
+    let inference = zkopml_client
+        .model_registry()
+        .getInference(inference_id)
+        .call()
+        .await?;
+    if inference.done {
+        info!("Inference {} was already responded", inference_id);
+        if inference.zk || inference.responder != user_address {
+            return Ok(None);
+        }
+        let model = load_onnx_model(&args.model_path)?;
+        let (_, hashes, _) = run_inference(args, &model, &inference.inputData)?;
+        let (_, hash) = *hashes.last().context("The model has no operators")?;
+        if hash != inference.outputDataHash {
+            // E.g. a random defect, which cannot be reproduced
+            warn!(
+                "Inference {} was responded with another output, its challenges are not responded",
+                inference_id
+            );
+            return Ok(None);
+        }
+        return Ok(Some(hashes));
+    }
+    let fee = zkopml_client.fee(inference_id).await?;
+    if fee < args.min_fee {
+        info!(
+            "Not responding to inference {}: fee of {} ETH is below the minimum fee of {} ETH",
+            inference_id,
+            format_ether(fee),
+            format_ether(args.min_fee)
+        );
+        return Ok(None);
+    }
+    // Previous responses may have locked the whole bond
+    if !inference.zk
+        && let Err(error) =
+            ensure_bond(zkopml_client, user_address, response_bond, "responding").await
+    {
+        info!("Not responding to inference {}: {}", inference_id, error);
+        return Ok(None);
+    }
+    let input_data = request.inputData;
+    info!(
+        "Model id: {}, Inference id: {}, Input data: {:?}",
+        model_id, inference_id, input_data
+    );
+
+    // Perform the inference
+    // TODO: read the model file from IPFS based on model id
+    // For now, we are going to assume there is only one model
+    info!("Reading the model file from {}", args.model_path);
+    let model_path = args.model_path.clone();
+    let model = load_onnx_model(&model_path)?;
+    let (initial_state, hashes, output_data) = run_inference(args, &model, &input_data)?;
+    let (_, hash) = *hashes.last().context("The model has no operators")?;
+
+    // Full-ZK inferences are responded with a proof of the whole inference, proven without
+    // blocking the responses to the challenges of the other inferences
+    if inference.zk {
+        let (args, zkopml_client) = (args.clone(), zkopml_client.clone());
+        tokio::spawn(async move {
+            if let Err(error) = respond_with_proof(
+                &args,
+                &zkopml_client,
+                &model,
+                initial_state,
+                inference_id,
+                output_data,
+                hash,
+            )
+            .await
+            {
+                error!(
+                    "Failed to respond to full-ZK inference {}: {:?}",
+                    inference_id, error
+                );
             }
-        } else {
-            None
-        };
+        });
+        // The result is final, there is nothing to challenge
+        return Ok(None);
+    }
 
-        for i in 0..model.num_operators() {
-            let node = model.get_node(i).unwrap();
+    // Submit the result
+    let responded = zkopml_client
+        .respond(
+            inference_id,
+            output_data,
+            hash.into(),
+            Bytes::new(),
+            Bytes::new(),
+        )
+        .await?;
+    if !responded {
+        info!("Inference {} was already responded", inference_id);
+        return Ok(None);
+    }
+    info!("Inference {} responded", inference_id);
 
-            if i == 0 {
-                for t in model.graph().clone().unwrap().initializer.iter() {
-                    let tensor = get_tensor(t, t.name.as_str())?;
-                    inputs.insert(t.name.to_string(), tensor);
-                }
-            }
+    Ok(Some(hashes))
+}
 
-            // Calculate hash of the input data
-            let mut input_hashes = HashMap::new();
+/// Proves all operators of a full-ZK inference, aggregates the proofs and responds to the
+/// inference with the aggregated proof.
+async fn respond_with_proof<P: Provider + Clone>(
+    args: &SubmitArgs,
+    zkopml_client: &ZkopmlClient<P>,
+    model: &Model,
+    initial_state: HashMap<String, Tensor>,
+    inference_id: U256,
+    output_data: Bytes,
+    hash: [u8; 32],
+) -> anyhow::Result<()> {
+    info!(
+        "Inference {} requires a ZK proof, proving all operators",
+        inference_id
+    );
+    let provider = zkopml_client.provider();
+    let model_registry = zkopml_client.model_registry();
+    let verifier = model_registry.SP1_VERIFIER().call().await?;
+    check_verifier(provider, verifier, args.proof_system).await?;
+    let merkle_tree = ModelMerkleTree::new(model.graph().unwrap().node, model.graph().unwrap());
+    let context = ProofContext::inference(
+        provider.get_chain_id().await?,
+        *model_registry.address(),
+        inference_id,
+    );
+    let client = SP1Client::new(args.sp1_prover)
+        .strategy(args.strategy)
+        .key_cache(KeyCache::new(&args.key_cache_dir));
+    // Check that the local programs are the deployed ones, before spending time on proving
+    let (_, vk) = client.setup(ELF).await?;
+    let (_, aggregate_vk) = client.setup(AGGREGATE_ELF).await?;
+    check_aggregate_program_vkey(provider, *model_registry.address(), &vk, &aggregate_vk).await?;
+    let (proof, _) = prove_aggregate(
+        &client,
+        model,
+        &merkle_tree,
+        initial_state,
+        0..=model.num_operators() - 1,
+        context,
+        args.proof_system,
+    )
+    .await?;
+
+    // Submit the result
+    let responded = zkopml_client
+        .respond(
+            inference_id,
+            output_data,
+            hash.into(),
+            Bytes::copy_from_slice(proof.public_values.as_slice()),
+            Bytes::copy_from_slice(&proof.bytes()),
+        )
+        .await?;
+    if !responded {
+        info!("Inference {} was already responded", inference_id);
+        return Ok(());
+    }
+    info!("Inference {} responded with a ZK proof", inference_id);
 
-            for (name, tensor) in inputs.iter() {
-                let hash = tensor_hash(&tensor);
-                input_hashes.insert(name.clone(), hash);
+    Ok(())
+}
+
+/// Performs the inference (with the defect of the arguments, if any) and returns the initial
+/// state, the (input, output) state hashes of each operator and the output data.
+fn run_inference(
+    args: &SubmitArgs,
+    model: &Model,
+    input_data: &Bytes,
+) -> anyhow::Result<(HashMap<String, Tensor>, Vec<([u8; 32], [u8; 32])>, Bytes)> {
+    let mut inputs: HashMap<String, Tensor> =
+        serde_json::from_slice(input_data).context("Invalid input data")?;
+
+    for (name, tensor) in &inputs {
+        match tensor.dtype() {
+            DType::F32 => {
+                info!(
+                    "Input tensor '{}': {:?}",
+                    name,
+                    tensor.flatten_all()?.to_vec1::<f32>()?
+                );
             }
-            let mut input_entries = input_hashes.iter().collect::<Vec<_>>();
-            input_entries.sort_by(|a, b| a.0.cmp(b.0));
-            let input_hash = hash_buffer(serde_json::to_string(&input_entries).unwrap().as_bytes());
-
-            simple_eval_one(&node, &mut inputs)?;
-
-            let mut defect_produced = false;
-
-            // Calculate hash of the output data
-            let mut input_hashes = HashMap::new();
-            for (name, tensor) in inputs.iter_mut() {
-                if let Some(defect_index) = defect_index {
-                    if !defect_produced && i == defect_index {
-                        if node.output.contains(name) {
-                            info!(
-                                "Augmenting the output data {} with a defect, index: {}",
-                                name, defect_index
-                            );
-                            let tensor_pow = tensor.powf(2.0f64)?;
-                            *tensor = tensor_pow;
-                            defect_produced = true;
-                        }
-                    }
-                }
-                let hash = tensor_hash(tensor);
-                input_hashes.insert(name.clone(), hash);
+            DType::F64 => {
+                info!(
+                    "Input tensor '{}': {:?}",
+                    name,
+                    tensor.flatten_all()?.to_vec1::<f64>()?
+                );
             }
-            let mut input_entries = input_hashes.iter().collect::<Vec<_>>();
-            input_entries.sort_by(|a, b| a.0.cmp(b.0));
-            let output_hash =
-                hash_buffer(serde_json::to_string(&input_entries).unwrap().as_bytes());
-
-            inference_hashes
-                .entry(inference_id)
-                .or_insert_with(Vec::new)
-                .push((input_hash, output_hash));
+            _ => {}
         }
+    }
 
-        let mut result = HashMap::new();
+    let mut initial_state = inputs.clone();
+    for t in model.graph().unwrap().initializer.iter() {
+        let tensor = get_tensor(t, t.name.as_str())?;
+        initial_state.insert(t.name.to_string(), tensor);
+    }
 
-        for output in model.graph().unwrap().output.iter() {
-            if let Some(tensor) = inputs.get(output.name.as_str()) {
-                info!(
-                    "Inference result for {}: {:?}",
-                    output.name,
-                    tensor.to_string()
-                );
-                result.insert(output.name.clone(), tensor.clone());
+    let mut hashes = Vec::new();
+    let graph_interface = GraphInterface::new(&model.graph().unwrap());
+
+    // If the defect flag is set, randomly select an operator to produce a defect
+    let defect_index = if args.defect {
+        if let Some(operator_index) = args.operator_index {
+            Some(operator_index as usize)
+        } else {
+            let mut rng = rand::rng();
+            let random_index = rng.random_range(0..model.num_operators());
+            Some(random_index)
+        }
+    } else {
+        None
+    };
+
+    for i in 0..model.num_operators() {
+        let node = model.get_node(i).unwrap();
+
+        if i == 0 {
+            for t in model.graph().clone().unwrap().initializer.iter() {
+                let tensor = get_tensor(t, t.name.as_str())?;
+                inputs.insert(t.name.to_string(), tensor);
             }
         }
 
+        // Calculate hash of the input data
         let mut input_hashes = HashMap::new();
+
         for (name, tensor) in inputs.iter() {
-            let hash = tensor_hash(tensor);
+            let hash = tensor_hash(&tensor);
             input_hashes.insert(name.clone(), hash);
         }
-        let mut input_entries = input_hashes.iter().collect::<Vec<_>>();
-        input_entries.sort_by(|a, b| a.0.cmp(b.0));
-        let hash = hash_buffer(serde_json::to_string(&input_entries).unwrap().as_bytes());
-
-        // Submit the result
-        let output_data =
-            Bytes::copy_from_slice(serde_json::to_string(&result).unwrap().as_bytes());
-        let model_registry = zkopml_contracts::ModelRegistry::new(
-            args.model_registry_address,
-            user_provider.clone(),
-        );
-        let tx = model_registry
-            .respondInference(inference_id, output_data, hash.into())
-            .send()
-            .await?;
-        info!("Transaction hash: {}", tx.tx_hash());
-        info!("Inference {} responded", inference_id);
-
-        std::thread::sleep(std::time::Duration::from_secs(10));
-
-        // Listen for challenge requests
-        let fault_proof =
-            zkopml_contracts::FaultProof::new(args.fault_proof_address, user_provider.clone());
-        let challenge_request_filter = Filter::new()
-            .address(args.fault_proof_address)
-            .from_block(BlockNumberOrTag::Latest);
-        let sub = user_provider
-            .subscribe_logs(&challenge_request_filter)
-            .await?;
-        let mut stream = sub.into_stream();
-
-        let mut challenge_ids: Vec<U256> = Vec::new();
-
-        while let Some(log) = stream.next().await {
-            // Parse the data
-            // TODO: support multiple challenge IDs
-            info!("Received challenge event: {:?}", log);
-            match log.topic0() {
-                Some(&ChallengeCreated::SIGNATURE_HASH) => {
-                    let request = ChallengeCreated::decode_log_data(log.data());
-                    if request.is_err() {
-                        info!("Failed to decode the request data");
-                        continue;
-                    }
-                    let request = request.unwrap();
-                    info!(
-                        "Challenge id {} for inference id {} created",
-                        request.challengeId, request.inferenceId
-                    );
-                    if inference_id == request.inferenceId {
-                        challenge_ids.push(request.challengeId);
-                    }
-                }
-                Some(&OperatorExecutionProposed::SIGNATURE_HASH) => {
-                    let request = OperatorExecutionProposed::decode_log_data(log.data());
-                    if request.is_err() {
-                        info!("Failed to decode the request data");
-                        continue;
-                    }
-                    let request = request.unwrap();
-                    std::thread::sleep(std::time::Duration::from_secs(2));
-                    if challenge_ids.contains(&request.challengeId) {
-                        let operator_position = request.operatorPosition;
-                        info!(
-                            "Operator execution proposed for challenge id {} at position {}",
-                            request.challengeId, request.operatorPosition
-                        );
-                        let (input_data_hash, output_data_hash) = inference_hashes
-                            .get(&inference_id)
-                            .unwrap()[operator_position.to::<usize>()];
-                        let input_data_match = input_data_hash == request.inputDataHash;
-                        let output_data_match = output_data_hash == request.outputDataHash;
+        let input_hash = state_hash(&input_hashes);
+
+        simple_eval_one(&node, &mut inputs)?;
+
+        let mut defect_produced = false;
+
+        // Calculate hash of the output data
+        let mut input_hashes = HashMap::new();
+        for (name, tensor) in inputs.iter_mut() {
+            if let Some(defect_index) = defect_index {
+                if !defect_produced && i == defect_index {
+                    if node.output.contains(name) {
                         info!(
-                            "Operator execution response for challenge id {} at position {}: {}, {}",
-                            request.challengeId,
-                            request.operatorPosition,
-                            input_data_match,
-                            output_data_match
+                            "Augmenting the output data {} with a defect, index: {}",
+                            name, defect_index
                         );
-                        let tx = fault_proof
-                            .respondOperatorExecution(
-                                request.challengeId,
-                                input_data_match,
-                                output_data_match,
-                            )
-                            .send()
-                            .await?;
-                        info!("Transaction hash: {}", tx.tx_hash());
-                        std::thread::sleep(std::time::Duration::from_secs(2));
-                    }
-                }
-                Some(&ChallengeResolved::SIGNATURE_HASH) => {
-                    let request = ChallengeResolved::decode_log_data(log.data());
-                    if request.is_err() {
-                        info!("Failed to decode the request data");
-                        continue;
+                        let tensor_pow = tensor.powf(2.0f64)?;
+                        *tensor = tensor_pow;
+                        defect_produced = true;
                     }
-                    let request = request.unwrap();
-                    info!(
-                        "Challenge id {} resolved, challenge actor winner: {}, address winner: {}",
-                        request.challengeId, request.success, request.winner
-                    );
-                }
-                _ => {
-                    // info!("Unknown event signature: {:?}", log);
                 }
             }
-
-            // TODO: handle expired challenges
+            let hash = tensor_hash(tensor);
+            input_hashes.insert(name.clone(), hash);
         }
+        // The final state is restricted to exactly the declared graph outputs
+        let output_hash = if i == model.num_operators() - 1 {
+            graph_interface
+                .outputs_hash(&input_hashes)
+                .ok_or_else(|| anyhow::anyhow!("Missing declared graph output"))?
+        } else {
+            state_hash(&input_hashes)
+        };
+
+        hashes.push((input_hash, output_hash));
     }
 
-    Ok(())
+    let mut result = HashMap::new();
+
+    for output in graph_interface.output_names() {
+        let tensor = inputs
+            .get(output.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing declared graph output {}", output))?;
+        info!("Inference result for {}: {:?}", output, tensor.to_string());
+        result.insert(output, tensor.clone());
+    }
+
+    let output_data = Bytes::copy_from_slice(serde_json::to_string(&result).unwrap().as_bytes());
+
+    Ok((initial_state, hashes, output_data))
 }
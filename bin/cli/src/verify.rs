@@ -1,22 +1,41 @@
 use alloy::{
     eips::BlockNumberOrTag,
     hex::ToHexExt,
-    network::EthereumWallet,
     primitives::{Address, Bytes, U256},
-    providers::{Provider, ProviderBuilder, WsConnect},
-    rpc::types::Filter,
-    signers::local::LocalSigner,
-    sol,
-    sol_types::SolEvent,
+    providers::{Provider, ProviderBuilder},
 };
+use anyhow::Context;
 use candle_core::{DType, Tensor};
 use candle_onnx::eval::{get_tensor, simple_eval_one};
-use futures_util::StreamExt;
-use sp1_sdk::{Prover, ProverClient, SP1Stdin, include_elf, network::FulfillmentStrategy};
-use std::{collections::HashMap, str::FromStr};
-use tracing::info;
+use sp1_sdk::{SP1ProvingKey, SP1Stdin, SP1VerifyingKey, include_elf};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+use tracing::{error, info, warn};
+use zkopml_contracts::{
+    CHALLENGE_ACTOR_RESPONDER, EventLog, EventStreamBuilder,
+    FaultProof::{FaultProofEvents, FaultProofInstance},
+    ModelRegistry::ModelRegistryEvents,
+    ZkopmlClient, ZkopmlEvent, fetch_events,
+};
 use zkopml_ml::{
-    data::tensor_hash, merkle::ModelMerkleTree, onnx::load_onnx_model, utils::hash_buffer,
+    data::{state_hash, tensor_hash},
+    interface::GraphInterface,
+    merkle::ModelMerkleTree,
+    onnx::{Model, load_onnx_model},
+    public_values::{ProofContext, PublicValues},
+    witness::OperatorWitness,
+};
+
+use crate::{
+    artifact::{Program, ProofArtifact, challenge_dir},
+    bond::ensure_bond,
+    keys::KeyCache,
+    manifest::{DeploymentManifest, contract_address},
+    proof_system::{ProofSystem, SP1Client, SP1Prover, Strategy, check_verifier},
+    provider, signer,
+    vkey::check_program_vkey,
 };
 
 #[derive(clap::Args, Debug, Clone)]
@@ -28,15 +47,20 @@ pub struct VerifyArgs {
     #[clap(long)]
     pub eth_node_address: String,
 
-    /// Address of the ModelRegistry contract
+    /// Path of the deployment manifest written by `deploy`, instead of the contract addresses
     #[clap(long)]
-    pub model_registry_address: Address,
+    pub deployment: Option<String>,
+
+    /// Address of the ModelRegistry contract
+    #[clap(long, required_unless_present = "deployment")]
+    pub model_registry_address: Option<Address>,
 
     /// Address of the FaultProof contract
-    #[clap(long)]
-    pub fault_proof_address: Address,
+    #[clap(long, required_unless_present = "deployment")]
+    pub fault_proof_address: Option<Address>,
 
-    /// Secret key to use for requesting the inference
+    /// Signer to use for challenging the inferences: hex private key, keystore:<path>, mnemonic:<path>
+    /// or remote:<url>
     #[clap(long)]
     pub user_key: String,
 
@@ -47,435 +71,698 @@ pub struct VerifyArgs {
     /// Path to the model file (ONNX)
     #[clap(long)]
     pub model_path: String,
-}
 
-sol!(
-    #[derive(Debug)]
-    event InferenceResponded(
-        uint256 modelId, uint256 inferenceId, address responder, bytes outputData, bytes32 outputDataHash
-    );
-    #[derive(Debug)]
-    event ChallengeCreated(uint256 challengeId, uint256 inferenceId, address responder, address challenger);
-    #[derive(Debug)]
-    event OperatorExecutionProposed(
-        uint256 challengeId, uint256 operatorPosition, bytes32 inputDataHash, bytes32 outputDataHash
-    );
-    #[derive(Debug)]
-    event OperatorExecutionResponded(uint256 challengeId, uint256 operatorPosition, bool input, bool output);
-    #[derive(Debug)]
-    event ChallengeResolved(uint256 challengeId, bool success, address winner);
-);
+    /// Type of SP1 prover of the proofs resolving challenges (`mock` requires the contracts to be
+    /// deployed with the SP1 mock verifier)
+    #[clap(long, default_value = "network")]
+    pub sp1_prover: SP1Prover,
+
+    /// SP1 proof system of the proofs resolving challenges
+    #[clap(long, default_value = "plonk")]
+    pub proof_system: ProofSystem,
+
+    /// Fulfillment strategy of the SP1 prover network
+    #[clap(long, default_value = "hosted")]
+    pub strategy: Strategy,
+
+    /// Directory of the SP1 proving/verifying key cache
+    #[clap(long, default_value = ".zkopml/keys")]
+    pub key_cache_dir: String,
+
+    /// Directory to save the proofs resolving challenges to, so the resolution can be retried
+    /// with the `resolve` command without re-proving
+    #[clap(long, default_value = "proofs")]
+    pub output_dir: String,
+
+    /// Block to backfill the inference responses from (defaults to the deploy block of the
+    /// deployment, otherwise to the next block)
+    #[clap(long)]
+    pub from_block: Option<u64>,
+
+    /// Number of confirmations of the blocks of the events before handling them
+    #[clap(long, default_value_t = 0)]
+    pub confirmations: u64,
+}
 
 const ELF: &[u8] = include_elf!("zkopml-zk");
 
 pub async fn verify(args: VerifyArgs) -> anyhow::Result<()> {
     // Initialize the user wallet
-    let user_signer = LocalSigner::from_str(&args.user_key)?;
-    let user_wallet = EthereumWallet::from(user_signer);
-    let ws_connect = WsConnect::new(args.eth_node_address);
+    let user_wallet = signer::wallet(&args.user_key).await?;
     let user_provider = ProviderBuilder::new()
         .wallet(&user_wallet)
-        .connect_ws(ws_connect)
-        .await?;
+        .connect_client(provider::rpc_client(&args.eth_node_address).await?);
     info!("User address: {}", user_wallet.default_signer().address());
 
-    // Listen for inference responses
-    let _model_registry =
-        zkopml_contracts::ModelRegistry::new(args.model_registry_address, user_provider.clone());
-    let inference_response_filter = Filter::new()
-        .address(args.model_registry_address)
-        .event("InferenceResponded(uint256,uint256,address,bytes,bytes32)")
-        .from_block(BlockNumberOrTag::Latest);
-    let sub = user_provider
-        .subscribe_logs(&inference_response_filter)
-        .await?;
-    let mut stream = sub.into_stream();
-
-    while let Some(log) = stream.next().await {
-        // Parse the data
-        info!("Received inference response: {:?}", log);
-        let response = InferenceResponded::decode_log_data(log.data());
-        if response.is_err() {
-            info!("Failed to decode the response data");
-            continue;
+    // Read the contract addresses from the deployment manifest, if any
+    let deployment = DeploymentManifest::load_optional(args.deployment.as_ref())?;
+    if let Some(deployment) = deployment.as_ref() {
+        deployment.check_chain_id(&user_provider).await?;
+    }
+    let model_registry_address = contract_address(
+        args.model_registry_address,
+        deployment.as_ref(),
+        |deployment| deployment.model_registry,
+        "model-registry-address",
+    )?;
+    let fault_proof_address = contract_address(
+        args.fault_proof_address,
+        deployment.as_ref(),
+        |deployment| deployment.fault_proof,
+        "fault-proof-address",
+    )?;
+
+    // Check that challenges can be resolved with the chosen proof system (the mock verifier
+    // accepts any mock proof)
+    if args.sp1_prover != SP1Prover::Mock {
+        let verifier =
+            zkopml_contracts::FaultProof::new(fault_proof_address, user_provider.clone())
+                .SP1_VERIFIER()
+                .call()
+                .await?;
+        check_verifier(&user_provider, verifier, args.proof_system).await?;
+    }
+
+    // Check that the local program is the deployed one, so proofs resolving challenges verify
+    let client = SP1Client::new(args.sp1_prover)
+        .strategy(args.strategy)
+        .key_cache(KeyCache::new(&args.key_cache_dir));
+    let (pk, vk) = client.setup(ELF).await?;
+    check_program_vkey(&user_provider, fault_proof_address, &vk).await?;
+
+    let zkopml_client = ZkopmlClient::new(user_provider.clone(), model_registry_address)
+        .with_fault_proof(fault_proof_address);
+    let fault_proof = zkopml_client.fault_proof()?;
+    let challenge_window = fault_proof.challengeWindow().call().await?;
+    // Challenges lock a challenge bond, refuse to start without one
+    let user_address = user_wallet.default_signer().address();
+    let challenge_bond = zkopml_client.challenge_bond().await?;
+    ensure_bond(&zkopml_client, user_address, challenge_bond, "challenging").await?;
+
+    // TODO: read the model file from IPFS based on model id
+    // For now, we are going to assume there is only one model
+    info!("Reading the model file from {}", args.model_path);
+    let model = load_onnx_model(&args.model_path)?;
+    let num_operators = model.num_operators();
+    let prover = Arc::new(ChallengeProver {
+        client,
+        pk,
+        vk,
+        model,
+        fault_proof: fault_proof.clone(),
+        chain_id: user_provider.get_chain_id().await?,
+        args: args.clone(),
+    });
+
+    // Listen for inference responses and for the challenges of the verifier
+    // Backfill the events since the deployment, unless another block is given
+    let deploy_block = deployment
+        .as_ref()
+        .map(|deployment| deployment.deploy_block);
+    let from_block = args.from_block.or(deploy_block);
+    // Inferences already challenged by the verifier, their backfilled challenges are followed
+    // instead of challenging them again
+    let mut challenged = HashSet::new();
+    if let Some(from_block) = from_block {
+        let to_block = user_provider.get_block_number().await?;
+        for event in
+            fetch_events(&user_provider, &[fault_proof_address], from_block, to_block).await?
+        {
+            if let ZkopmlEvent::FaultProof(FaultProofEvents::ChallengeCreated(request)) =
+                event.event
+                && request.challenger == user_address
+            {
+                challenged.insert(request.inferenceId);
+            }
         }
-        let response = response.unwrap();
-        info!("Decoded response: {:?}", response);
-        let model_id: U256 = response.modelId;
-        let inference_id: U256 = response.inferenceId;
-        info!(
-            "Model id: {}, Inference id: {}, Output data: {:?}",
-            model_id, inference_id, response.outputData
-        );
+    }
+    let mut events = EventStreamBuilder::new(
+        user_provider.clone(),
+        vec![model_registry_address, fault_proof_address],
+    )
+    .from_block(from_block)
+    .confirmations(args.confirmations)
+    .start();
+
+    let mut verifier = Verifier {
+        zkopml_client,
+        fault_proof,
+        prover,
+        user_address,
+        challenge_bond,
+        challenge_window,
+        num_operators,
+        challenged,
+        inference_data: HashMap::new(),
+        inference_hashes: HashMap::new(),
+        challenges: HashMap::new(),
+    };
+    while let Some(event) = events.next().await {
+        let event = event?;
+        let (block_number, transaction_hash) = (event.block_number, event.transaction_hash);
+        // A failing event (e.g. a response to malformed input data) must not stop the verifier
+        if let Err(error) = verifier.handle(event).await {
+            warn!(
+                "Failed to handle the event of transaction {} in block {}: {:#}",
+                transaction_hash, block_number, error
+            );
+        }
+    }
 
-        // Get the inference input data
-        let model_registry = zkopml_contracts::ModelRegistry::new(
-            args.model_registry_address,
-            user_provider.clone(),
-        );
-        let inference = model_registry.getInference(inference_id).call().await?;
-        let input_data = inference.inputData;
-        info!("Inference input data: {:?}", input_data);
-
-        // Perform the inference
-        // TODO: read the model file from IPFS based on model id
-        // For now, we are going to assume there is only one model
-        info!("Reading the model file from {}", args.model_path);
-        let model_path = args.model_path.clone();
-        let model = load_onnx_model(&model_path)?;
-
-        let mut inputs: HashMap<String, Tensor> = serde_json::from_slice(&input_data).unwrap();
-
-        for (name, tensor) in &inputs {
-            match tensor.dtype() {
-                DType::F32 => {
+    // The event stream retries the failures of the node, it only ends if it was stopped
+    anyhow::bail!("The event stream ended")
+}
+
+/// State of the verifier, updated by the events of the contracts.
+struct Verifier<P> {
+    zkopml_client: ZkopmlClient<P>,
+    fault_proof: FaultProofInstance<P>,
+    prover: Arc<ChallengeProver<P>>,
+    user_address: Address,
+    challenge_bond: U256,
+    challenge_window: U256,
+    num_operators: usize,
+    /// Inferences challenged by the verifier, including the backfilled challenges
+    challenged: HashSet<U256>,
+    /// Operator states and hashes of the challenged inferences, by inference id
+    inference_data: HashMap<U256, Vec<HashMap<String, Tensor>>>,
+    inference_hashes: HashMap<U256, Vec<([u8; 32], [u8; 32])>>,
+    /// Bisection state of the challenges of the verifier, by challenge id (other challengers of
+    /// the same inference bisect independently)
+    challenges: HashMap<U256, ChallengeState>,
+}
+
+impl<P: Provider + Clone + 'static> Verifier<P> {
+    /// Challenges the faulty inference responses and bisects the challenges of the verifier.
+    async fn handle(&mut self, event: EventLog) -> anyhow::Result<()> {
+        match event.event {
+            ZkopmlEvent::ModelRegistry(ModelRegistryEvents::InferenceResponded(response)) => {
+                info!(
+                    "Received inference response in block {}: {:?}",
+                    event.block_number, response
+                );
+                let inference_id: U256 = response.inferenceId;
+                info!(
+                    "Model id: {}, Inference id: {}, Output data: {:?}",
+                    response.modelId, inference_id, response.outputData
+                );
+                if self.challenged.contains(&inference_id) {
+                    info!("Inference {} was already challenged", inference_id);
+                    return Ok(());
+                }
+
+                // Get the inference input data
+                let inference = self
+                    .zkopml_client
+                    .model_registry()
+                    .getInference(inference_id)
+                    .call()
+                    .await?;
+                if inference.zk {
                     info!(
-                        "Input tensor '{}': {:?}",
-                        name,
-                        tensor.flatten_all()?.to_vec1::<f32>()?
+                        "Inference {} was responded with a ZK proof verified on-chain, the result is final",
+                        inference_id
                     );
+                    return Ok(());
+                }
+                // Backfilled responses may be out of the challenge window already
+                let latest_block = self
+                    .zkopml_client
+                    .provider()
+                    .get_block_by_number(BlockNumberOrTag::Latest)
+                    .await?
+                    .context("Missing the latest block")?;
+                if inference.timestampResponse + self.challenge_window
+                    <= U256::from(latest_block.header.timestamp)
+                {
+                    info!("The challenge window of inference {} expired", inference_id);
+                    return Ok(());
+                }
+                // Another challenger may have proven the fault already
+                if self
+                    .fault_proof
+                    .faultyInferences(inference_id)
+                    .call()
+                    .await?
+                {
+                    info!("Inference {} was already proven faulty", inference_id);
+                    return Ok(());
                 }
-                DType::F64 => {
+                info!("Inference input data: {:?}", inference.inputData);
+
+                // Perform the inference
+                let (data, hashes, output_data) =
+                    run_inference(&self.prover.model, &inference.inputData)?;
+                let (_, hash) = *hashes.last().context("The model has no operators")?;
+
+                // Compare the result with the expected output
+                if output_data == response.outputData && hash == response.outputDataHash {
+                    info!("Output data matches the expected result, not challenging");
+                    return Ok(());
+                }
+
+                info!(
+                    "Output data does not match the expected result, challenging inference {}",
+                    inference_id
+                );
+                // Previous challenges may have locked the whole bond
+                if let Err(error) = ensure_bond(
+                    &self.zkopml_client,
+                    self.user_address,
+                    self.challenge_bond,
+                    "challenging",
+                )
+                .await
+                {
+                    info!("Not challenging inference {}: {}", inference_id, error);
+                    return Ok(());
+                }
+                tokio::time::sleep(std::time::Duration::from_secs(10)).await;
+
+                // Create challenge
+                let challenge_id = self.zkopml_client.create_challenge(inference_id).await?;
+                info!("Challenge created with id: {}", challenge_id);
+                self.challenged.insert(inference_id);
+
+                // Propose first operator execution
+                let state = ChallengeState {
+                    inference_id,
+                    low: 0,
+                    high: self.num_operators - 1,
+                };
+                propose(&self.fault_proof, challenge_id, &state, &hashes).await?;
+                self.challenges.insert(challenge_id, state);
+                self.inference_data.insert(inference_id, data);
+                self.inference_hashes.insert(inference_id, hashes);
+            }
+            ZkopmlEvent::FaultProof(FaultProofEvents::ChallengeCreated(request)) => {
+                if request.challenger != self.user_address
+                    || self.challenges.contains_key(&request.challengeId)
+                {
+                    return Ok(());
+                }
+                // Backfilled challenge of the verifier, restore its bisection state
+                info!(
+                    "Following challenge id {} for inference id {}",
+                    request.challengeId, request.inferenceId
+                );
+                let inference_id = request.inferenceId;
+                if !self.inference_hashes.contains_key(&inference_id) {
+                    let inference = self
+                        .zkopml_client
+                        .model_registry()
+                        .getInference(inference_id)
+                        .call()
+                        .await?;
+                    let (data, hashes, _) =
+                        run_inference(&self.prover.model, &inference.inputData)?;
+                    self.inference_data.insert(inference_id, data);
+                    self.inference_hashes.insert(inference_id, hashes);
+                }
+                let state = ChallengeState {
+                    inference_id,
+                    low: 0,
+                    high: self.num_operators - 1,
+                };
+                // The first operator execution may not have been proposed
+                if awaits_challenger(&self.zkopml_client, request.challengeId, &state).await? {
+                    propose(
+                        &self.fault_proof,
+                        request.challengeId,
+                        &state,
+                        &self.inference_hashes[&inference_id],
+                    )
+                    .await?;
+                }
+                self.challenges.insert(request.challengeId, state);
+            }
+            ZkopmlEvent::FaultProof(FaultProofEvents::OperatorExecutionResponded(response)) => {
+                let Some(state) = self.challenges.get_mut(&response.challengeId) else {
+                    return Ok(());
+                };
+                tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+                let challenge_id = response.challengeId;
+                let input_data_match = response.input;
+                let output_data_match = response.output;
+                info!(
+                    "Operator execution response for challenge id {}: input data match: {}, output data match: {}",
+                    challenge_id, input_data_match, output_data_match
+                );
+                let mid = state.mid();
+                match (input_data_match, output_data_match) {
+                    (true, true) => {
+                        // Move right
+                        state.low = mid + 1;
+                        if !awaits_challenger(&self.zkopml_client, challenge_id, state).await? {
+                            // Backfilled response, the next execution was proposed already
+                            return Ok(());
+                        }
+                        propose(
+                            &self.fault_proof,
+                            challenge_id,
+                            state,
+                            &self.inference_hashes[&state.inference_id],
+                        )
+                        .await?;
+                    }
+                    (true, false) => {
+                        if !awaits_challenger(&self.zkopml_client, challenge_id, state).await? {
+                            // Backfilled response, the challenge was resolved already
+                            return Ok(());
+                        }
+                        // Do the SP1 zkVM verification, without blocking the other challenges
+                        let prover = self.prover.clone();
+                        let operator_state = self.inference_data[&state.inference_id][mid].clone();
+                        let operator_hashes = self.inference_hashes[&state.inference_id][mid];
+                        tokio::spawn(async move {
+                            if let Err(error) = prover
+                                .resolve(challenge_id, mid, &operator_state, operator_hashes)
+                                .await
+                            {
+                                error!("Failed to resolve challenge {}: {:?}", challenge_id, error);
+                            }
+                        });
+                    }
+                    (false, false) | (false, true) => {
+                        // Move left
+                        state.high = mid - 1;
+                        if !awaits_challenger(&self.zkopml_client, challenge_id, state).await? {
+                            // Backfilled response, the next execution was proposed already
+                            return Ok(());
+                        }
+                        propose(
+                            &self.fault_proof,
+                            challenge_id,
+                            state,
+                            &self.inference_hashes[&state.inference_id],
+                        )
+                        .await?;
+                    }
+                }
+            }
+            ZkopmlEvent::FaultProof(FaultProofEvents::InferenceFaulty(request)) => {
+                // The first won challenge settles the inference, the other challenges of the
+                // verifier are resolved in its favour
+                let settled: Vec<U256> = self
+                    .challenges
+                    .iter()
+                    .filter(|(challenge_id, state)| {
+                        state.inference_id == request.inferenceId
+                            && **challenge_id != request.challengeId
+                    })
+                    .map(|(challenge_id, _)| *challenge_id)
+                    .collect();
+                for challenge_id in settled {
+                    if self.zkopml_client.challenge(challenge_id).await?.resolved {
+                        continue;
+                    }
                     info!(
-                        "Input tensor '{}': {:?}",
-                        name,
-                        tensor.flatten_all()?.to_vec1::<f64>()?
+                        "Inference {} was proven faulty by challenge id {}, resolving challenge id {}",
+                        request.inferenceId, request.challengeId, challenge_id
                     );
+                    let tx = self
+                        .fault_proof
+                        .resolveFaultyChallenge(challenge_id)
+                        .send()
+                        .await?;
+                    info!("Transaction hash: {}", tx.tx_hash());
                 }
-                _ => {}
             }
-        }
-
-        let mut inference_data: HashMap<U256, Vec<HashMap<String, Tensor>>> = HashMap::new();
-        let mut inference_data_hashes: HashMap<U256, Vec<HashMap<String, [u8; 32]>>> =
-            HashMap::new();
-        let mut inference_hashes: HashMap<U256, Vec<([u8; 32], [u8; 32])>> = HashMap::new();
-
-        for i in 0..model.num_operators() {
-            if i == 0 {
-                for t in model.graph().clone().unwrap().initializer.iter() {
-                    let tensor = get_tensor(t, t.name.as_str())?;
-                    inputs.insert(t.name.to_string(), tensor);
+            ZkopmlEvent::FaultProof(FaultProofEvents::ChallengeResolved(request)) => {
+                let Some(state) = self.challenges.remove(&request.challengeId) else {
+                    return Ok(());
+                };
+                info!(
+                    "Challenge id {} resolved, challenge actor winner: {}, address winner: {}",
+                    request.challengeId, request.success, request.winner
+                );
+                if !self
+                    .challenges
+                    .values()
+                    .any(|other| other.inference_id == state.inference_id)
+                {
+                    self.inference_data.remove(&state.inference_id);
+                    self.inference_hashes.remove(&state.inference_id);
                 }
             }
+            _ => {}
+        }
 
-            inference_data
-                .entry(inference_id)
-                .or_insert_with(Vec::new)
-                .push(inputs.clone());
-
-            // Calculate hash of the input data
-            let mut input_hashes = HashMap::new();
-            for (name, tensor) in inputs.iter() {
-                let hash = tensor_hash(tensor);
-                input_hashes.insert(name.clone(), hash);
-            }
+        Ok(())
+    }
+}
 
-            inference_data_hashes
-                .entry(inference_id)
-                .or_insert_with(Vec::new)
-                .push(input_hashes.clone());
+/// Bisection state of a challenge of the verifier.
+struct ChallengeState {
+    inference_id: U256,
+    low: usize,
+    high: usize,
+}
 
-            let mut input_entries = input_hashes.iter().collect::<Vec<_>>();
-            input_entries.sort_by(|a, b| a.0.cmp(b.0));
-            let input_hash = hash_buffer(serde_json::to_string(&input_entries).unwrap().as_bytes());
+impl ChallengeState {
+    /// Operator of the next proposed execution.
+    fn mid(&self) -> usize {
+        (self.low + self.high) / 2
+    }
+}
 
-            let node = model.get_node(i).unwrap();
-            simple_eval_one(&node, &mut inputs)?;
+/// Returns whether a challenge waits for an action of the verifier at the given bisection state
+/// (the verifier may have acted on the backfilled events already, in a previous run).
+async fn awaits_challenger<P: Provider + Clone>(
+    zkopml_client: &ZkopmlClient<P>,
+    challenge_id: U256,
+    state: &ChallengeState,
+) -> anyhow::Result<bool> {
+    let challenge = zkopml_client.challenge(challenge_id).await?;
+
+    Ok(!challenge.resolved
+        && u8::from(challenge.lastActor) == CHALLENGE_ACTOR_RESPONDER
+        && challenge.operatorLow == U256::from(state.low)
+        && challenge.operatorHigh == U256::from(state.high))
+}
 
-            // Calculate hash of the output data
-            let mut input_hashes = HashMap::new();
-            for (name, tensor) in inputs.iter() {
-                let hash = tensor_hash(tensor);
-                input_hashes.insert(name.clone(), hash);
-            }
+/// Proposes the operator execution at the middle of the bisection range of a challenge.
+async fn propose<P: Provider>(
+    fault_proof: &FaultProofInstance<P>,
+    challenge_id: U256,
+    state: &ChallengeState,
+    hashes: &[([u8; 32], [u8; 32])],
+) -> anyhow::Result<()> {
+    let mid = state.mid();
+    let (input_data_hash, output_data_hash) = hashes[mid];
+    let tx = fault_proof
+        .proposeOperatorExecution(
+            challenge_id,
+            input_data_hash.into(),
+            output_data_hash.into(),
+        )
+        .send()
+        .await?;
+    info!("Transaction hash: {}", tx.tx_hash());
+    info!(
+        "Operator execution for operator {} proposed with input data hash: {:?}, output data hash: {:?}",
+        mid,
+        input_data_hash.encode_hex(),
+        output_data_hash.encode_hex()
+    );
 
-            let mut input_entries = input_hashes.iter().collect::<Vec<_>>();
-            input_entries.sort_by(|a, b| a.0.cmp(b.0));
-            let output_hash =
-                hash_buffer(serde_json::to_string(&input_entries).unwrap().as_bytes());
+    Ok(())
+}
 
-            inference_hashes
-                .entry(inference_id)
-                .or_insert_with(Vec::new)
-                .push((input_hash, output_hash));
+/// Performs the inference and returns the state before each operator, the (input, output) state
+/// hashes of each operator and the output data.
+fn run_inference(
+    model: &Model,
+    input_data: &Bytes,
+) -> anyhow::Result<(
+    Vec<HashMap<String, Tensor>>,
+    Vec<([u8; 32], [u8; 32])>,
+    Bytes,
+)> {
+    let mut inputs: HashMap<String, Tensor> =
+        serde_json::from_slice(input_data).context("Invalid input data")?;
+
+    for (name, tensor) in &inputs {
+        match tensor.dtype() {
+            DType::F32 => {
+                info!(
+                    "Input tensor '{}': {:?}",
+                    name,
+                    tensor.flatten_all()?.to_vec1::<f32>()?
+                );
+            }
+            DType::F64 => {
+                info!(
+                    "Input tensor '{}': {:?}",
+                    name,
+                    tensor.flatten_all()?.to_vec1::<f64>()?
+                );
+            }
+            _ => {}
         }
+    }
 
-        let mut result = HashMap::new();
+    let mut data = Vec::new();
+    let mut hashes = Vec::new();
+    let graph_interface = GraphInterface::new(&model.graph().unwrap());
 
-        for output in model.graph().unwrap().output.iter() {
-            if let Some(tensor) = inputs.get(output.name.as_str()) {
-                info!(
-                    "Inference result for {}: {:?}",
-                    output.name,
-                    tensor.to_string()
-                );
-                result.insert(output.name.clone(), tensor.clone());
+    for i in 0..model.num_operators() {
+        if i == 0 {
+            for t in model.graph().clone().unwrap().initializer.iter() {
+                let tensor = get_tensor(t, t.name.as_str())?;
+                inputs.insert(t.name.to_string(), tensor);
             }
         }
 
+        data.push(inputs.clone());
+
+        // Calculate hash of the input data
         let mut input_hashes = HashMap::new();
         for (name, tensor) in inputs.iter() {
             let hash = tensor_hash(tensor);
             input_hashes.insert(name.clone(), hash);
         }
-        let mut input_entries = input_hashes.iter().collect::<Vec<_>>();
-        input_entries.sort_by(|a, b| a.0.cmp(b.0));
-        let hash = hash_buffer(serde_json::to_string(&input_entries).unwrap().as_bytes());
-
-        // Compare the result with the expected output
-        let output_data =
-            Bytes::copy_from_slice(serde_json::to_string(&result).unwrap().as_bytes());
-        if output_data == response.outputData && hash == response.outputDataHash {
-            info!("Output data matches the expected result, not challenging");
-            continue;
+
+        let input_hash = state_hash(&input_hashes);
+
+        let node = model.get_node(i).unwrap();
+        simple_eval_one(&node, &mut inputs)?;
+
+        // Calculate hash of the output data
+        let mut input_hashes = HashMap::new();
+        for (name, tensor) in inputs.iter() {
+            let hash = tensor_hash(tensor);
+            input_hashes.insert(name.clone(), hash);
         }
 
+        // The final state is restricted to exactly the declared graph outputs
+        let output_hash = if i == model.num_operators() - 1 {
+            graph_interface
+                .outputs_hash(&input_hashes)
+                .ok_or_else(|| anyhow::anyhow!("Missing declared graph output"))?
+        } else {
+            state_hash(&input_hashes)
+        };
+
+        hashes.push((input_hash, output_hash));
+    }
+
+    let mut result = HashMap::new();
+
+    for output in graph_interface.output_names() {
+        let tensor = inputs
+            .get(output.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing declared graph output {}", output))?;
+        info!("Inference result for {}: {:?}", output, tensor.to_string());
+        result.insert(output, tensor.clone());
+    }
+    let output_data = Bytes::copy_from_slice(serde_json::to_string(&result).unwrap().as_bytes());
+
+    Ok((data, hashes, output_data))
+}
+
+/// Proves disputed operator executions and resolves the challenges of the verifier.
+struct ChallengeProver<P> {
+    client: SP1Client,
+    pk: SP1ProvingKey,
+    vk: SP1VerifyingKey,
+    model: Model,
+    fault_proof: FaultProofInstance<P>,
+    chain_id: u64,
+    args: VerifyArgs,
+}
+
+impl<P: Provider> ChallengeProver<P> {
+    /// Proves the execution of the operator a challenge converged to and resolves the challenge.
+    async fn resolve(
+        &self,
+        challenge_id: U256,
+        operator_index: usize,
+        state: &HashMap<String, Tensor>,
+        (input_data_hash, output_data_hash): ([u8; 32], [u8; 32]),
+    ) -> anyhow::Result<()> {
+        let model = &self.model;
+        let merkle_tree = ModelMerkleTree::new(model.graph().unwrap().node, model.graph().unwrap());
+        let node = model.get_node(operator_index).unwrap();
+        // Bind the proof to this challenge, so it cannot be replayed
+        let context =
+            ProofContext::challenge(self.chain_id, *self.fault_proof.address(), challenge_id);
+        let witness = OperatorWitness::builder(model, &merkle_tree)
+            .operator_index(operator_index)
+            .state(state)
+            .context(context.clone())
+            .build()?;
+        let mut stdin = SP1Stdin::new();
+        stdin.write(&witness);
+
+        info!(
+            "Executing the SP1 program. Proving ONNX operator: {:?}",
+            node
+        );
+
+        let (public_values, report) = self.client.execute(ELF, &stdin).await?;
         info!(
-            "Output data does not match the expected result, challenging inference {}",
-            inference_id
+            "executed program with {} cycles",
+            report.total_instruction_count()
         );
-        std::thread::sleep(std::time::Duration::from_secs(10));
-        let fault_proof =
-            zkopml_contracts::FaultProof::new(args.fault_proof_address, user_provider.clone());
 
-        let mut challenges: Vec<U256> = Vec::new();
+        info!("Raw public values: {:?}", public_values.raw());
 
-        // Create challenge
-        let tx = fault_proof.createChallenge(inference_id).send().await?;
-        info!("Transaction hash: {}", tx.tx_hash());
-        std::thread::sleep(std::time::Duration::from_secs(10));
-        let challenge_id = fault_proof.challengeCounter().call().await? - U256::from(1);
-        info!("Challenge created with id: {}", challenge_id);
-
-        challenges.push(challenge_id);
-
-        // Propose first operator execution
-        let mut low = 0;
-        let mut high = model.num_operators() - 1;
-        let mut mid = (low + high) / 2;
-        let (input_data_hash, output_data_hash) = inference_hashes.get(&inference_id).unwrap()[mid];
-        let tx = fault_proof
-            .proposeOperatorExecution(
+        // Check the public values before spending time on proving
+        PublicValues::decode(public_values.as_slice())?.verify(
+            &context,
+            merkle_tree.root(),
+            merkle_tree.num_operators(),
+            operator_index,
+            input_data_hash,
+            output_data_hash,
+        )?;
+
+        self.client.register_program(&self.vk, ELF).await?;
+
+        let proof = self
+            .client
+            .prove(&self.pk, &stdin, self.args.proof_system.mode())
+            .await?;
+        info!("generated proof");
+
+        let proof_bytes = proof.bytes();
+
+        info!(
+            "Resolving the challenge id {} for operator {} with SP1 proof verification (public values: {}, proof: {})",
+            challenge_id,
+            operator_index,
+            public_values.raw(),
+            proof.bytes().encode_hex()
+        );
+        let proof_dir = challenge_dir(&self.args.output_dir, challenge_id);
+        ProofArtifact::new(
+            proof,
+            self.vk.clone(),
+            Program::Operator,
+            self.args.sp1_prover,
+            self.args.proof_system,
+        )?
+        .save(&proof_dir)?;
+
+        let tx = self
+            .fault_proof
+            .resolveOpenChallenge(
                 challenge_id,
-                input_data_hash.into(),
-                output_data_hash.into(),
+                Bytes::copy_from_slice(public_values.as_slice()),
+                Bytes::copy_from_slice(&proof_bytes),
             )
             .send()
-            .await?;
+            .await
+            .with_context(|| {
+                format!(
+                    "Failed to resolve challenge {}, retry with `resolve --proof-dir {}`",
+                    challenge_id,
+                    proof_dir.display()
+                )
+            })?;
         info!("Transaction hash: {}", tx.tx_hash());
-        info!(
-            "Operator execution for operator {} proposed with input data hash: {:?}, output data hash: {:?}",
-            mid,
-            input_data_hash.encode_hex(),
-            output_data_hash.encode_hex()
-        );
 
-        // Listen for challenge requests
-        let challenge_request_filter = Filter::new()
-            .address(args.fault_proof_address)
-            .from_block(BlockNumberOrTag::Latest);
-        let sub = user_provider
-            .subscribe_logs(&challenge_request_filter)
-            .await?;
-        let mut stream = sub.into_stream();
-
-        while let Some(log) = stream.next().await {
-            // Parse the data
-            // TODO: support multiple challenge IDs
-            info!("Received challenge event: {:?}", log);
-            match log.topic0() {
-                Some(&OperatorExecutionResponded::SIGNATURE_HASH) => {
-                    let response = OperatorExecutionResponded::decode_log_data(log.data());
-                    if response.is_err() {
-                        info!("Failed to decode the response data");
-                        continue;
-                    }
-                    let response = response.unwrap();
-                    std::thread::sleep(std::time::Duration::from_secs(2));
-                    if challenges.contains(&response.challengeId) {
-                        let input_data_match = response.input;
-                        let output_data_match = response.output;
-                        info!(
-                            "Operator execution response: input data match: {}, output data match: {}",
-                            input_data_match, output_data_match
-                        );
-                        match (input_data_match, output_data_match) {
-                            (true, true) => {
-                                // Move right
-                                low = mid + 1;
-                                mid = (low + high) / 2;
-                                let (input_data_hash, output_data_hash) =
-                                    inference_hashes.get(&inference_id).unwrap()[mid];
-                                info!(
-                                    "Operator execution for operator {} proposed with input data hash: {:?}, output data hash: {:?}",
-                                    mid,
-                                    input_data_hash.encode_hex(),
-                                    output_data_hash.encode_hex()
-                                );
-                                let tx = fault_proof
-                                    .proposeOperatorExecution(
-                                        challenge_id,
-                                        input_data_hash.into(),
-                                        output_data_hash.into(),
-                                    )
-                                    .send()
-                                    .await?;
-                                info!("Transaction hash: {}", tx.tx_hash());
-                                std::thread::sleep(std::time::Duration::from_secs(2));
-                            }
-                            (true, false) => {
-                                // Do the SP1 zkVM verification
-                                let mut stdin = SP1Stdin::new();
-
-                                // Write the merkle tree root hash
-                                let merkle_tree = ModelMerkleTree::new(
-                                    model.graph().unwrap().node,
-                                    model.graph().unwrap(),
-                                );
-                                stdin.write(&merkle_tree.root());
-
-                                // Write the index of the operator
-                                let leaf_indices = vec![mid];
-                                stdin.write(&leaf_indices);
-
-                                // Write the hashes of the leaves
-                                let leaf_hashes = merkle_tree.leaves_hashes(leaf_indices.clone());
-                                stdin.write(&leaf_hashes);
-
-                                // Write the total number of leaves
-                                let total_leaves = merkle_tree.total_leaves();
-                                stdin.write(&total_leaves);
-
-                                // Write the merkle proof
-                                let merkle_proof: Vec<u8> =
-                                    merkle_tree.prove(leaf_indices).to_bytes();
-                                stdin.write(&merkle_proof);
-
-                                // Write inputs
-                                let node = model.get_node(mid).unwrap();
-                                let mut inputs =
-                                    inference_data.get(&inference_id).unwrap()[mid].clone();
-                                inputs.retain(|k: &String, _| node.input.contains(k));
-                                let mut inputs_raw: HashMap<String, Tensor> = HashMap::new();
-                                for (name, tensor) in inputs.iter() {
-                                    let mut init = false;
-                                    for t in model.graph().unwrap().initializer.iter() {
-                                        if name == &t.name {
-                                            let mut input_name = name.clone();
-                                            input_name.push_str("graph_initializer");
-                                            inputs_raw.insert(input_name, tensor.clone());
-                                            init = true;
-                                            break;
-                                        }
-                                    }
-                                    if !init {
-                                        inputs_raw.insert(name.clone(), tensor.clone());
-                                    }
-                                }
-                                stdin.write(&inputs_raw);
-
-                                // Write inputs hashes
-                                stdin
-                                    .write(&inference_data_hashes.get(&inference_id).unwrap()[mid]);
-
-                                // Write node
-                                stdin.write(&node);
-
-                                info!("Using the network SP1 prover.");
-                                let client = ProverClient::builder().network().build();
-                                info!(
-                                    "Executing the SP1 program. Proving ONNX operator: {:?}",
-                                    node
-                                );
-
-                                let (public_values, report) =
-                                    client.execute(ELF, &stdin).run().unwrap();
-                                info!(
-                                    "executed program with {} cycles",
-                                    report.total_instruction_count()
-                                );
-
-                                info!("Raw public values: {:?}", public_values.raw());
-
-                                let (pk, vk) = client.setup(ELF);
-                                info!("generated keys (setup)");
-
-                                let program_hash = client.register_program(&vk, ELF).await?;
-                                info!("registered program with hash: {:?}", program_hash);
-
-                                let proof = client
-                                    .prove(&pk, &stdin)
-                                    .cycle_limit(1_000_000_000)
-                                    .strategy(FulfillmentStrategy::Hosted)
-                                    .skip_simulation(true)
-                                    .plonk()
-                                    .run()
-                                    .unwrap();
-                                info!("generated proof");
-
-                                let proof_bytes = proof.bytes();
-
-                                info!(
-                                    "Resolving the challenge id {} for operator {} with SP1 proof verification (public values: {}, proof: {})",
-                                    challenge_id,
-                                    mid,
-                                    public_values.raw(),
-                                    proof.bytes().encode_hex()
-                                );
-                                let tx = fault_proof
-                                    .resolveOpenChallenge(
-                                        challenge_id,
-                                        Bytes::from_str(public_values.raw().as_str()).unwrap(),
-                                        Bytes::copy_from_slice(&proof_bytes),
-                                    )
-                                    .send()
-                                    .await?;
-                                info!("Transaction hash: {}", tx.tx_hash());
-                            }
-                            (false, false) | (false, true) => {
-                                // Move left
-                                high = mid - 1;
-                                mid = (low + high) / 2;
-                                let (input_data_hash, output_data_hash) =
-                                    inference_hashes.get(&inference_id).unwrap()[mid];
-                                info!(
-                                    "Operator execution for operator {} proposed with input data hash: {:?}, output data hash: {:?}",
-                                    mid,
-                                    input_data_hash.encode_hex(),
-                                    output_data_hash.encode_hex()
-                                );
-                                let tx = fault_proof
-                                    .proposeOperatorExecution(
-                                        challenge_id,
-                                        input_data_hash.into(),
-                                        output_data_hash.into(),
-                                    )
-                                    .send()
-                                    .await?;
-                                info!("Transaction hash: {}", tx.tx_hash());
-                            }
-                        }
-                    }
-                }
-                Some(&ChallengeResolved::SIGNATURE_HASH) => {
-                    let request = ChallengeResolved::decode_log_data(log.data());
-                    if request.is_err() {
-                        info!("Failed to decode the request data");
-                        continue;
-                    }
-                    let request = request.unwrap();
-                    info!(
-                        "Challenge id {} resolved, challenge actor winner: {}, address winner: {}",
-                        request.challengeId, request.success, request.winner
-                    );
-                }
-                _ => {
-                    // info!("Unknown event signature: {:?}", log);
-                }
-            }
-        }
+        Ok(())
     }
-
-    Ok(())
 }
@@ -0,0 +1,114 @@
+use alloy::hex::ToHexExt;
+use sp1_sdk::{HashableKey, include_elf};
+use tracing::info;
+use zkopml_ml::public_values::{AggregatePublicValues, PublicValues};
+
+use crate::{
+    artifact::{Program, ProofArtifact},
+    keys::KeyCache,
+    proof_system::{SP1Client, SP1Prover},
+};
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct VerifyProofArgs {
+    #[arg(long, short, help = "Verbosity level (0-4)", action = clap::ArgAction::Count)]
+    pub v: u8,
+
+    /// Directory of the saved proof (written by `prove --output-dir` or `verify`)
+    #[clap(long)]
+    pub proof_dir: String,
+
+    /// Directory of the SP1 proving/verifying key cache
+    #[clap(long, default_value = ".zkopml/keys")]
+    pub key_cache_dir: String,
+}
+
+const ELF: &[u8] = include_elf!("zkopml-zk");
+const AGGREGATE_ELF: &[u8] = include_elf!("zkopml-zk-aggregate");
+
+pub async fn verify_proof(args: VerifyProofArgs) -> anyhow::Result<()> {
+    info!("Reading the proof from {}", args.proof_dir);
+    let artifact = ProofArtifact::load(&args.proof_dir)?;
+    info!("Proof metadata: {:?}", artifact.metadata);
+
+    // Mock proofs are only verified by the mock prover
+    let client = SP1Client::new(match artifact.metadata.sp1_prover {
+        SP1Prover::Mock => SP1Prover::Mock,
+        SP1Prover::Cpu | SP1Prover::Network => SP1Prover::Cpu,
+    })
+    .key_cache(KeyCache::new(&args.key_cache_dir));
+    let elf = match artifact.metadata.program {
+        Program::Operator => ELF,
+        Program::Aggregate => AGGREGATE_ELF,
+    };
+
+    // The proof must verify against the program built locally, not only the saved key
+    let (_, vk) = client.setup(elf).await?;
+    info!("Local vk: {}", vk.bytes32());
+    anyhow::ensure!(
+        vk.bytes32() == artifact.vk.bytes32() && vk.bytes32() == artifact.metadata.vkey,
+        "The proof was generated for vk {}, the local {:?} program has vk {}",
+        artifact.metadata.vkey,
+        artifact.metadata.program,
+        vk.bytes32()
+    );
+    client.verify(&artifact.proof, &vk)?;
+    info!("Verified proof");
+
+    let public_values = artifact.proof.public_values.as_slice();
+    match artifact.metadata.program {
+        Program::Operator => {
+            let public_values = PublicValues::decode(public_values)?;
+            info!("Public values:");
+            info!(
+                "Context: chain id {}, verifier {}, domain {}, id {}",
+                public_values.context.chainId,
+                public_values.context.verifier,
+                public_values.context.domain,
+                public_values.context.id
+            );
+            info!("Merkle root: {:?}", public_values.merkleRoot.encode_hex());
+            info!("Number of operators: {}", public_values.numOperators);
+            info!("Operator index: {}", public_values.operatorIndex);
+            info!(
+                "Inputs hash: {:?}",
+                public_values.inputDataHash.encode_hex()
+            );
+            info!(
+                "Outputs hash: {:?}",
+                public_values.outputDataHash.encode_hex()
+            );
+        }
+        Program::Aggregate => {
+            let public_values = AggregatePublicValues::decode(public_values)?;
+            info!("Aggregate public values:");
+            info!(
+                "Context: chain id {}, verifier {}, domain {}, id {}",
+                public_values.context.chainId,
+                public_values.context.verifier,
+                public_values.context.domain,
+                public_values.context.id
+            );
+            info!(
+                "Operator program vkey: {:?}",
+                public_values.operatorVkey.encode_hex()
+            );
+            info!("Merkle root: {:?}", public_values.merkleRoot.encode_hex());
+            info!("Number of operators: {}", public_values.numOperators);
+            info!(
+                "Operators: {} to {}",
+                public_values.firstOperator, public_values.lastOperator
+            );
+            info!(
+                "Inputs hash: {:?}",
+                public_values.inputDataHash.encode_hex()
+            );
+            info!(
+                "Outputs hash: {:?}",
+                public_values.outputDataHash.encode_hex()
+            );
+        }
+    }
+
+    Ok(())
+}
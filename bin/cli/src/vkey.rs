@@ -0,0 +1,144 @@
+use alloy::{
+    hex::ToHexExt,
+    primitives::{Address, B256},
+    providers::{Provider, ProviderBuilder},
+};
+use sp1_sdk::{HashableKey, SP1VerifyingKey, include_elf};
+use tracing::info;
+use zkopml_ml::public_values::vkey_digest;
+
+use crate::{
+    keys::KeyCache,
+    manifest::DeploymentManifest,
+    proof_system::{SP1Client, SP1Prover},
+    provider,
+};
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct VkeyArgs {
+    #[arg(long, short, help = "Verbosity level (0-4)", action = clap::ArgAction::Count)]
+    pub v: u8,
+
+    /// Address of the Ethereum node endpoint to use (to compare with the deployed contracts)
+    #[clap(long)]
+    pub eth_node_address: Option<String>,
+
+    /// Path of the deployment manifest written by `deploy`, to compare with its contracts
+    #[clap(long, requires = "eth_node_address")]
+    pub deployment: Option<String>,
+
+    /// Address of the FaultProof contract
+    #[clap(long, requires = "eth_node_address")]
+    pub fault_proof_address: Option<Address>,
+
+    /// Address of the ModelRegistry contract
+    #[clap(long, requires = "eth_node_address")]
+    pub model_registry_address: Option<Address>,
+
+    /// Directory of the SP1 proving/verifying key cache
+    #[clap(long, default_value = ".zkopml/keys")]
+    pub key_cache_dir: String,
+}
+
+const ELF: &[u8] = include_elf!("zkopml-zk");
+const AGGREGATE_ELF: &[u8] = include_elf!("zkopml-zk-aggregate");
+
+pub async fn vkey(args: VkeyArgs) -> anyhow::Result<()> {
+    let client = SP1Client::new(SP1Prover::Cpu).key_cache(KeyCache::new(&args.key_cache_dir));
+    let (_, vk) = client.setup(ELF).await?;
+    let (_, aggregate_vk) = client.setup(AGGREGATE_ELF).await?;
+    info!("Operator program vkey: {}", vk.bytes32());
+    info!(
+        "Operator program vkey digest: 0x{}",
+        vkey_digest(&vk.hash_u32()).encode_hex()
+    );
+    info!("Aggregation program vkey: {}", aggregate_vk.bytes32());
+
+    let Some(eth_node_address) = args.eth_node_address else {
+        return Ok(());
+    };
+    let provider =
+        ProviderBuilder::new().connect_client(provider::rpc_client(&eth_node_address).await?);
+    let deployment = DeploymentManifest::load_optional(args.deployment.as_ref())?;
+    if let Some(deployment) = deployment.as_ref() {
+        deployment.check_chain_id(&provider).await?;
+    }
+    let fault_proof_address = args
+        .fault_proof_address
+        .or(deployment.as_ref().map(|manifest| manifest.fault_proof));
+    let model_registry_address = args
+        .model_registry_address
+        .or(deployment.as_ref().map(|manifest| manifest.model_registry));
+    if let Some(fault_proof_address) = fault_proof_address {
+        check_program_vkey(&provider, fault_proof_address, &vk).await?;
+    }
+    if let Some(model_registry_address) = model_registry_address {
+        check_aggregate_program_vkey(&provider, model_registry_address, &vk, &aggregate_vk).await?;
+    }
+
+    Ok(())
+}
+
+/// Checks that the local operator program is the one the FaultProof contract verifies proofs of,
+/// before spending time on proving.
+pub async fn check_program_vkey<P: Provider>(
+    provider: &P,
+    fault_proof_address: Address,
+    vk: &SP1VerifyingKey,
+) -> anyhow::Result<()> {
+    let program_vkey = zkopml_contracts::FaultProof::new(fault_proof_address, provider)
+        .PROGRAM_VKEY()
+        .call()
+        .await?;
+    let local_vkey = B256::from(vk.bytes32_raw());
+    anyhow::ensure!(
+        program_vkey == local_vkey,
+        "The local operator program (vkey {}) does not match the PROGRAM_VKEY {} of FaultProof {}, rebuild the program from the deployed version",
+        local_vkey,
+        program_vkey,
+        fault_proof_address
+    );
+    info!(
+        "Operator program vkey matches FaultProof {}",
+        fault_proof_address
+    );
+
+    Ok(())
+}
+
+/// Checks that the local aggregation and operator programs are the ones the ModelRegistry
+/// contract verifies full-ZK inference proofs of.
+pub async fn check_aggregate_program_vkey<P: Provider>(
+    provider: &P,
+    model_registry_address: Address,
+    vk: &SP1VerifyingKey,
+    aggregate_vk: &SP1VerifyingKey,
+) -> anyhow::Result<()> {
+    let model_registry = zkopml_contracts::ModelRegistry::new(model_registry_address, provider);
+
+    let aggregate_program_vkey = model_registry.AGGREGATE_PROGRAM_VKEY().call().await?;
+    let local_vkey = B256::from(aggregate_vk.bytes32_raw());
+    anyhow::ensure!(
+        aggregate_program_vkey == local_vkey,
+        "The local aggregation program (vkey {}) does not match the AGGREGATE_PROGRAM_VKEY {} of ModelRegistry {}",
+        local_vkey,
+        aggregate_program_vkey,
+        model_registry_address
+    );
+
+    let operator_vkey_digest = model_registry.OPERATOR_PROGRAM_VKEY_DIGEST().call().await?;
+    let local_digest = B256::from(vkey_digest(&vk.hash_u32()));
+    anyhow::ensure!(
+        operator_vkey_digest == local_digest,
+        "The local operator program (vkey digest {}) does not match the OPERATOR_PROGRAM_VKEY_DIGEST {} of ModelRegistry {}",
+        local_digest,
+        operator_vkey_digest,
+        model_registry_address
+    );
+    info!(
+        "Aggregation and operator program vkeys match ModelRegistry {}",
+        model_registry_address
+    );
+
+    Ok(())
+}
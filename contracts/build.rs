@@ -2,8 +2,75 @@ use foundry_compilers::{
     artifacts::Settings, multi::MultiCompilerSettings, solc::SolcSettings, Project,
     ProjectPathsConfig,
 };
+use serde_json::{json, Value};
+use std::path::Path;
 
+/// Contracts the Rust bindings are generated for.
+const CONTRACTS: [&str; 3] = ["ModelRegistry", "FaultProof", "SP1MockVerifier"];
+
+/// Directory of the checked-in ABI and bytecode artifacts.
+const ARTIFACTS_DIR: &str = "artifacts";
+
+/// With the default `prebuilt-artifacts` feature, the bindings are generated from the checked-in
+/// artifacts and solc is not needed; the contracts are only compiled (and the artifacts written)
+/// when the artifacts are missing. Without the feature, the contracts are always compiled and the
+/// artifacts refreshed. `check-artifacts` compiles them and fails if the artifacts are stale,
+/// without writing them.
 fn main() {
+    println!("cargo:rerun-if-changed={ARTIFACTS_DIR}");
+
+    let check = cfg!(feature = "check-artifacts");
+    if cfg!(feature = "prebuilt-artifacts") && !check && artifacts_exist() {
+        // Use the checked-in artifacts, no solc is needed
+        return;
+    }
+    if cfg!(feature = "prebuilt-artifacts") && !check {
+        println!(
+            "cargo:warning=Missing contract artifacts in {ARTIFACTS_DIR}, compiling the contracts"
+        );
+    }
+
+    compile();
+
+    let mut stale = Vec::new();
+    for contract in CONTRACTS {
+        let artifact = compiled_artifact(contract);
+        let path = artifact_path(contract);
+        let checked_in: Option<Value> = std::fs::read(&path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok());
+        if checked_in.as_ref() == Some(&artifact) {
+            continue;
+        }
+        if check {
+            stale.push(contract);
+        } else {
+            std::fs::create_dir_all(ARTIFACTS_DIR).expect("failed to create artifacts directory");
+            let mut bytes =
+                serde_json::to_vec_pretty(&artifact).expect("failed to encode artifact");
+            bytes.push(b'\n');
+            std::fs::write(&path, bytes).expect("failed to write artifact");
+        }
+    }
+
+    if !stale.is_empty() {
+        panic!(
+            "The checked-in artifacts of {stale:?} are stale, update them with `just update-contract-artifacts`"
+        );
+    }
+}
+
+fn artifacts_exist() -> bool {
+    CONTRACTS
+        .iter()
+        .all(|contract| Path::new(&artifact_path(contract)).exists())
+}
+
+fn artifact_path(contract: &str) -> String {
+    format!("{ARTIFACTS_DIR}/{contract}.json")
+}
+
+fn compile() {
     // configure the project with all its paths, solc, cache etc.
     let project_paths = ProjectPathsConfig::builder().build_with_root("foundry");
     let mut settings = MultiCompilerSettings::default();
@@ -32,3 +99,18 @@ fn main() {
     println!("cargo:rerun-if-changed=src");
     println!("cargo:rerun-if-changed=test");
 }
+
+/// Reads the ABI and bytecode of a compiled contract from the Foundry output.
+fn compiled_artifact(contract: &str) -> Value {
+    let path = format!("foundry/out/{contract}.sol/{contract}.json");
+    let artifact: Value = serde_json::from_slice(
+        &std::fs::read(&path).unwrap_or_else(|_| panic!("missing compiled artifact {path}")),
+    )
+    .expect("failed to decode compiled artifact");
+
+    json!({
+        "abi": artifact["abi"],
+        "bytecode": { "object": artifact["bytecode"]["object"] },
+        "deployedBytecode": { "object": artifact["deployedBytecode"]["object"] },
+    })
+}
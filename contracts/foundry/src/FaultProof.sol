@@ -51,6 +51,22 @@ struct OperatorExecution {
     bytes32 outputDataHash;
 }
 
+/// @notice Public values committed by the SP1 program (ABI-encoded).
+struct PublicValues {
+    /// @notice Challenge the proof is bound to.
+    ProofContext context;
+    /// @notice Merkle root of the model operators.
+    bytes32 merkleRoot;
+    /// @notice Number of ONNX operators of the model (the Merkle tree has one more header leaf).
+    uint256 numOperators;
+    /// @notice Index of the executed ONNX operator (Merkle leaf index).
+    uint256 operatorIndex;
+    /// @notice Input data hash of the operator execution.
+    bytes32 inputDataHash;
+    /// @notice Output data hash of the operator execution.
+    bytes32 outputDataHash;
+}
+
 /// @notice Emitted when a challenge is created.
 event ChallengeCreated(uint256 challengeId, uint256 inferenceId, address responder, address challenger);
 
@@ -65,6 +81,13 @@ event OperatorExecutionResponded(uint256 challengeId, uint256 operatorPosition,
 /// @notice Emitted when a challenge is resolved.
 event ChallengeResolved(uint256 challengeId, bool success, address winner);
 
+/// @notice Emitted when the first challenge of an inference is won, settling the inference as faulty for all its
+/// challenges.
+event InferenceFaulty(uint256 inferenceId, uint256 challengeId, address challenger);
+
+/// @notice Emitted when the bond of the losing actor of a challenge is slashed.
+event BondSlashed(uint256 challengeId, address account, uint256 amount);
+
 contract FaultProof {
     /// @notice Model registry.
     IModelRegistry internal immutable MODEL_REGISTRY;
@@ -81,6 +104,9 @@ contract FaultProof {
     /// @notice SP1 program verification key.
     bytes32 public immutable PROGRAM_VKEY;
 
+    /// @notice Bond locked by the challenger until the challenge is resolved.
+    uint256 public immutable CHALLENGE_BOND;
+
     /// @notice Counter of challenges.
     uint256 public challengeCounter;
 
@@ -90,6 +116,12 @@ contract FaultProof {
     /// @notice Mapping of all operator executions.
     mapping(uint256 => mapping(uint256 => OperatorExecution)) public operatorExecutions;
 
+    /// @notice Mapping of the number of unresolved challenges of all inferences.
+    mapping(uint256 => uint256) public openChallenges;
+
+    /// @notice Mapping of the inferences settled as faulty by a won challenge.
+    mapping(uint256 => bool) public faultyInferences;
+
     /// @notice Returns the address of the model registry.
     function modelRegistry() public view returns (IModelRegistry modelRegistry_) {
         modelRegistry_ = MODEL_REGISTRY;
@@ -105,18 +137,25 @@ contract FaultProof {
         responseWindow_ = RESPONSE_WINDOW;
     }
 
+    /// @notice Returns the challenge bond.
+    function challengeBond() public view returns (uint256 challengeBond_) {
+        challengeBond_ = CHALLENGE_BOND;
+    }
+
     constructor(
         IModelRegistry _modelRegistry,
         uint256 _challengeWindow,
         uint256 _responseWindow,
         address _sp1Verifier,
-        bytes32 _programVKey
+        bytes32 _programVKey,
+        uint256 _challengeBond
     ) {
         MODEL_REGISTRY = _modelRegistry;
         CHALLENGE_WINDOW = _challengeWindow;
         RESPONSE_WINDOW = _responseWindow;
         SP1_VERIFIER = _sp1Verifier;
         PROGRAM_VKEY = _programVKey;
+        CHALLENGE_BOND = _challengeBond;
     }
 
     /// @notice Returns the challenge with the given id.
@@ -125,11 +164,18 @@ contract FaultProof {
     }
 
     /// @notice Creates/opens a new challenge.
+    /// @dev Locks `CHALLENGE_BOND` of the challenger's bond in the model registry. An inference can have any number of
+    /// concurrent challenges, each with its own bisection state, until one of them is won.
     function createChallenge(uint256 inferenceId) public returns (uint256 challengeId) {
         Inference memory inference = MODEL_REGISTRY.getInference(inferenceId);
 
+        require(!inference.zk, "zk inference cannot be challenged");
         require(inference.done, "inference not responded yet");
         require(inference.timestampResponse + CHALLENGE_WINDOW > block.timestamp, "challenge window expired");
+        require(!faultyInferences[inferenceId], "inference already faulty");
+
+        MODEL_REGISTRY.lockBond(msg.sender, CHALLENGE_BOND);
+        openChallenges[inferenceId] = openChallenges[inferenceId] + 1;
 
         uint256 modelId = inference.modelId;
         Model memory model = MODEL_REGISTRY.getModel(modelId);
@@ -260,41 +306,43 @@ contract FaultProof {
         ISP1Verifier(SP1_VERIFIER).verifyProof(PROGRAM_VKEY, publicValues, proofBytes);
 
         // Verify the public commitments of the proof
-        bytes32 merkleRoot = bytes32(publicValues[:66]);
-        bytes16 leafIndices = bytes16(publicValues[32:66]);
-        bytes32 inputDataHash = bytes32(publicValues[48:80]);
-        bytes32 outputDataHash = bytes32(publicValues[80:112]);
+        PublicValues memory values = decodePublicValues(publicValues);
 
         uint256 mid = (challenges[challengeId].operatorLow + challenges[challengeId].operatorHigh) / 2;
         uint256 modelId = MODEL_REGISTRY.getInference(challenges[challengeId].inferenceId).modelId;
         Model memory model = MODEL_REGISTRY.getModel(modelId);
 
+        // Verify the proof is bound to this challenge
+        require(values.context.chainId == block.chainid, "chain id does not match");
+        require(values.context.verifier == address(this), "fault proof address does not match");
+        require(values.context.domain == PROOF_DOMAIN_CHALLENGE, "proof domain does not match");
+        require(values.context.id == challengeId, "challenge id does not match");
+
         // Verify merkle root
-        require(merkleRoot == model.root, "merkle root does not match");
+        require(values.merkleRoot == model.root, "merkle root does not match");
+        require(values.numOperators == model.numOperators, "number of operators does not match");
 
-        // Verify leaf indices
+        // Verify leaf index
         // TODO: support execution of multiple ONNX operators
-        uint256 leaf_index = littleToBigEndian(leafIndices);
-        require(leaf_index == mid, "leaf index does not match current ONNX operator");
+        require(values.operatorIndex == mid, "leaf index does not match current ONNX operator");
 
         // Verify input data hash
         require(
             operatorExecutions[challengeId][mid].inputDataHash != bytes32(0)
-                && inputDataHash == operatorExecutions[challengeId][mid].inputDataHash,
+                && values.inputDataHash == operatorExecutions[challengeId][mid].inputDataHash,
             "input data hash does not match"
         );
 
         // Verify output data hash
         require(
             operatorExecutions[challengeId][mid].outputDataHash != bytes32(0)
-                && outputDataHash == operatorExecutions[challengeId][mid].outputDataHash,
+                && values.outputDataHash == operatorExecutions[challengeId][mid].outputDataHash,
             "output data hash does not match"
         );
 
         challenges[challengeId].winner = ChallengeActor.CHALLENGER;
-        // TODO: slash the responder
-
         challenges[challengeId].resolved = true;
+        settleBonds(challengeId);
 
         emit ChallengeResolved(
             challengeId, challenges[challengeId].winner == ChallengeActor.CHALLENGER, challenges[challengeId].challenger
@@ -303,6 +351,7 @@ contract FaultProof {
 
     // This can be called by responder to resolve the expired challenge.
     function resolveExpiredChallenge(uint256 challengeId) public {
+        require(challengeId < challengeCounter, "challenge does not exist");
         require(
             challenges[challengeId].timestampAction + RESPONSE_WINDOW < block.timestamp,
             "challenge window not expired yet"
@@ -311,33 +360,81 @@ contract FaultProof {
 
         address winner;
 
-        if (challenges[challengeId].lastActor == ChallengeActor.RESPONDER || challenges[challengeId].ready) {
+        // The responder cannot win a challenge of an inference another challenger proved faulty
+        bool faulty = faultyInferences[challenges[challengeId].inferenceId];
+        if (
+            !faulty
+                && (challenges[challengeId].lastActor == ChallengeActor.RESPONDER || challenges[challengeId].ready)
+        ) {
             challenges[challengeId].winner = ChallengeActor.RESPONDER;
             winner = challenges[challengeId].responder;
-            // TODO: slash the challenger
         } else {
             challenges[challengeId].winner = ChallengeActor.CHALLENGER;
             winner = challenges[challengeId].challenger;
-            // TODO: slash the responder
         }
 
         challenges[challengeId].resolved = true;
+        settleBonds(challengeId);
 
         emit ChallengeResolved(challengeId, challenges[challengeId].winner == ChallengeActor.CHALLENGER, winner);
     }
 
-    function littleToBigEndian(bytes16 input) public pure returns (uint256) {
-        // Extract the last 8 bytes by shifting
-        bytes8 last8Bytes = bytes8(input << 64);
-        uint256 result = 0;
+    /// @notice Resolves an open challenge of an inference settled as faulty by another challenge, in favour of its
+    /// challenger (the challenge bond is returned).
+    function resolveFaultyChallenge(uint256 challengeId) public {
+        require(challengeId < challengeCounter, "challenge does not exist");
+        require(!challenges[challengeId].resolved, "challenge already resolved");
+        require(faultyInferences[challenges[challengeId].inferenceId], "inference not faulty");
+
+        challenges[challengeId].winner = ChallengeActor.CHALLENGER;
+        challenges[challengeId].resolved = true;
+        settleBonds(challengeId);
+
+        emit ChallengeResolved(challengeId, true, challenges[challengeId].challenger);
+    }
 
-        uint256 mul = 1;
+    /// @notice Releases the response bond and the escrowed fee of an inference to its responder once the challenge
+    /// window expired and all its challenges are resolved (both already went to the challenger if a challenger won).
+    function releaseResponseBond(uint256 inferenceId) public {
+        Inference memory inference = MODEL_REGISTRY.getInference(inferenceId);
 
-        for (uint256 i = 0; i < 8; i++) {
-            result += uint256(uint8(last8Bytes[i])) * mul;
-            mul *= 16 * 16;
+        require(!inference.zk, "zk inference has no response bond");
+        require(inference.done, "inference not responded yet");
+        require(inference.timestampResponse + CHALLENGE_WINDOW <= block.timestamp, "challenge window not expired yet");
+        require(openChallenges[inferenceId] == 0, "inference has open challenges");
+
+        MODEL_REGISTRY.releaseResponseBond(inferenceId);
+        MODEL_REGISTRY.releaseFee(inferenceId, inference.responder);
+    }
+
+    /// @notice Settles the bonds of a resolved challenge.
+    /// @dev A winning challenger gets the challenge bond back. The first one also gets the slashed response bond and
+    /// the escrowed fee, and settles the inference as faulty, so the responder cannot win its other challenges. A
+    /// winning responder gets the challenge bond. Challenges are independent otherwise: a lost (e.g. colluding)
+    /// challenge does not affect the other challenges of the inference.
+    function settleBonds(uint256 challengeId) internal {
+        Challenge memory challenge = challenges[challengeId];
+        openChallenges[challenge.inferenceId] = openChallenges[challenge.inferenceId] - 1;
+
+        if (challenge.winner == ChallengeActor.CHALLENGER && !faultyInferences[challenge.inferenceId]) {
+            faultyInferences[challenge.inferenceId] = true;
+            uint256 slashed = MODEL_REGISTRY.slashResponseBond(challenge.inferenceId);
+            MODEL_REGISTRY.unlockBond(challenge.challenger, CHALLENGE_BOND + slashed);
+            MODEL_REGISTRY.releaseFee(challenge.inferenceId, challenge.challenger);
+
+            emit InferenceFaulty(challenge.inferenceId, challengeId, challenge.challenger);
+            emit BondSlashed(challengeId, challenge.responder, slashed);
+        } else if (challenge.winner == ChallengeActor.CHALLENGER) {
+            MODEL_REGISTRY.unlockBond(challenge.challenger, CHALLENGE_BOND);
+        } else {
+            MODEL_REGISTRY.unlockBond(challenge.responder, CHALLENGE_BOND);
+
+            emit BondSlashed(challengeId, challenge.challenger, CHALLENGE_BOND);
         }
+    }
 
-        return result;
+    /// @notice Decodes the ABI-encoded public values of the SP1 program.
+    function decodePublicValues(bytes calldata publicValues) public pure returns (PublicValues memory values) {
+        values = abi.decode(publicValues, (PublicValues));
     }
 }
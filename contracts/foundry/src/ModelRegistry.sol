@@ -3,6 +3,8 @@ pragma solidity ^0.8.24;
 
 import "./interfaces/IModelRegistry.sol";
 
+import {ISP1Verifier} from "sp1-contracts/src/ISP1Verifier.sol";
+
 /// @notice Emitted when new model is registered.
 event ModelRegistered(uint256 id, string uri, address registrar, bytes32 root, uint256 numOperators);
 
@@ -16,6 +18,15 @@ event InferenceResponded(
     uint256 modelId, uint256 inferenceId, address responder, bytes outputData, bytes32 outputDataHash
 );
 
+/// @notice Emitted when a bond is deposited.
+event BondDeposited(address account, uint256 amount);
+
+/// @notice Emitted when a bond is withdrawn.
+event BondWithdrawn(address account, uint256 amount);
+
+/// @notice Emitted when the escrowed fee of an inference is released.
+event FeeReleased(uint256 inferenceId, address account, uint256 amount);
+
 contract ModelRegistry is IModelRegistry {
     /// @notice Semantic version.
     /// @custom:sermver 0.1.0
@@ -33,6 +44,108 @@ contract ModelRegistry is IModelRegistry {
     /// @notice Mapping of all inferences.
     mapping(uint256 => Inference) public inferences;
 
+    /// @notice Address of the SP1 verifier contract.
+    address public immutable SP1_VERIFIER;
+
+    /// @notice SP1 aggregation program verification key (full-ZK inferences).
+    bytes32 public immutable AGGREGATE_PROGRAM_VKEY;
+
+    /// @notice Verification key digest of the SP1 operator program, committed by the aggregation program.
+    bytes32 public immutable OPERATOR_PROGRAM_VKEY_DIGEST;
+
+    /// @notice Bond locked by the responder of an optimistic inference, slashed if the response is successfully
+    /// challenged.
+    uint256 public immutable RESPONSE_BOND;
+
+    /// @notice Owner of the contract, sets the FaultProof contract.
+    address public immutable OWNER;
+
+    /// @notice FaultProof contract locking and settling the bonds of challenges.
+    address public faultProof;
+
+    /// @notice Mapping of the free (withdrawable) bonds and released fees of all accounts.
+    mapping(address => uint256) public bonds;
+
+    /// @notice Mapping of the bonds locked by the responders of optimistic inferences.
+    mapping(uint256 => uint256) public responseBonds;
+
+    /// @notice Mapping of the fees escrowed by the requesters of inferences.
+    mapping(uint256 => uint256) public fees;
+
+    modifier onlyFaultProof() {
+        require(msg.sender == faultProof, "only fault proof");
+        _;
+    }
+
+    constructor(
+        address _sp1Verifier,
+        bytes32 _aggregateProgramVKey,
+        bytes32 _operatorProgramVKeyDigest,
+        uint256 _responseBond,
+        address _owner
+    ) {
+        SP1_VERIFIER = _sp1Verifier;
+        AGGREGATE_PROGRAM_VKEY = _aggregateProgramVKey;
+        OPERATOR_PROGRAM_VKEY_DIGEST = _operatorProgramVKeyDigest;
+        RESPONSE_BOND = _responseBond;
+        OWNER = _owner;
+    }
+
+    /// @notice Sets the FaultProof contract (once, by the owner).
+    function setFaultProof(address _faultProof) public {
+        require(msg.sender == OWNER, "only owner");
+        require(faultProof == address(0), "fault proof already set");
+        faultProof = _faultProof;
+    }
+
+    /// @notice Deposits a bond for the sender.
+    function depositBond() public payable {
+        bonds[msg.sender] += msg.value;
+
+        emit BondDeposited(msg.sender, msg.value);
+    }
+
+    /// @notice Withdraws a part of the free bond of the sender.
+    /// @dev Bonds locked by responses are freed with `FaultProof.releaseResponseBond` after the challenge window.
+    function withdrawBond(uint256 amount) public {
+        require(bonds[msg.sender] >= amount, "insufficient bond");
+        bonds[msg.sender] -= amount;
+
+        (bool success,) = msg.sender.call{value: amount}("");
+        require(success, "bond transfer failed");
+
+        emit BondWithdrawn(msg.sender, amount);
+    }
+
+    /// @notice Locks a part of the free bond of an account (challenger bonds).
+    function lockBond(address account, uint256 amount) external onlyFaultProof {
+        require(bonds[account] >= amount, "insufficient bond");
+        bonds[account] -= amount;
+    }
+
+    /// @notice Frees a locked bond amount to an account (bond payouts).
+    function unlockBond(address account, uint256 amount) external onlyFaultProof {
+        bonds[account] += amount;
+    }
+
+    /// @notice Releases the escrowed fee of an inference to an account (responder or successful challenger).
+    function releaseFee(uint256 inferenceId, address account) external onlyFaultProof {
+        payFee(inferenceId, account);
+    }
+
+    /// @notice Slashes the response bond of an inference, returns the slashed amount.
+    function slashResponseBond(uint256 inferenceId) external onlyFaultProof returns (uint256 amount) {
+        amount = responseBonds[inferenceId];
+        responseBonds[inferenceId] = 0;
+    }
+
+    /// @notice Frees the response bond of an inference to its responder.
+    function releaseResponseBond(uint256 inferenceId) external onlyFaultProof {
+        uint256 amount = responseBonds[inferenceId];
+        responseBonds[inferenceId] = 0;
+        bonds[inferences[inferenceId].responder] += amount;
+    }
+
     /// @notice Registers a new model.
     function registerModel(string memory uri, bytes32 root, uint256 numOperators) public returns (uint256 modelId) {
         modelId = modelCounter;
@@ -48,30 +161,62 @@ contract ModelRegistry is IModelRegistry {
     }
 
     /// @notice Requests an inference for a model.
+    /// @dev With `zk` set, the inference must be responded with a ZK proof of the whole inference and is final
+    /// immediately, otherwise it is verified optimistically (FaultProof). The sent value is escrowed as the fee of
+    /// the inference, paid to the responder (on response for full-ZK inferences, after the challenge window
+    /// otherwise) or to the first successful challenger.
     // TODO: inputData should be URI reference to IPFS
-    function requestInference(uint256 modelId, bytes calldata inputData, bytes32 inputDataHash)
+    function requestInference(uint256 modelId, bytes calldata inputData, bytes32 inputDataHash, bool zk)
         public
+        payable
         returns (uint256 inferenceId)
     {
         inferenceId = inferenceCounter;
         inferenceCounter = inferenceCounter + 1;
         inferences[inferenceId] = Inference(
-            inferenceId, block.timestamp, 0, false, msg.sender, address(0), modelId, inputData, inputDataHash, "", ""
+            inferenceId,
+            block.timestamp,
+            0,
+            false,
+            msg.sender,
+            address(0),
+            modelId,
+            inputData,
+            inputDataHash,
+            "",
+            "",
+            zk
         );
+        fees[inferenceId] = msg.value;
 
         emit InferenceRequested(modelId, inferenceId, msg.sender, inputData, inputDataHash);
     }
 
     /// @notice Responds to an inference request.
+    /// @dev Full-ZK inferences require an aggregated SP1 proof of all model operators (public values and proof),
+    /// optimistic inferences ignore them and lock `RESPONSE_BOND` of the responder instead.
     // TODO: outputData should be URI reference to IPFS
-    function respondInference(uint256 inferenceId, bytes calldata outputData, bytes32 outputDataHash)
-        public
-        returns (bool success)
-    {
+    function respondInference(
+        uint256 inferenceId,
+        bytes calldata outputData,
+        bytes32 outputDataHash,
+        bytes calldata publicValues,
+        bytes calldata proofBytes
+    ) public returns (bool success) {
         if (inferences[inferenceId].done) {
             return false;
         }
 
+        if (inferences[inferenceId].zk) {
+            verifyInferenceProof(inferenceId, outputDataHash, publicValues, proofBytes);
+            // The result is final, the fee is paid immediately
+            payFee(inferenceId, msg.sender);
+        } else {
+            require(bonds[msg.sender] >= RESPONSE_BOND, "insufficient bond");
+            bonds[msg.sender] -= RESPONSE_BOND;
+            responseBonds[inferenceId] = RESPONSE_BOND;
+        }
+
         inferences[inferenceId].timestampResponse = block.timestamp;
         inferences[inferenceId].done = true;
         inferences[inferenceId].responder = msg.sender;
@@ -87,4 +232,59 @@ contract ModelRegistry is IModelRegistry {
     function getInference(uint256 inferenceId) public view returns (Inference memory inference) {
         return inferences[inferenceId];
     }
+
+    /// @notice Credits the escrowed fee of an inference to an account.
+    function payFee(uint256 inferenceId, address account) internal {
+        uint256 amount = fees[inferenceId];
+        if (amount == 0) {
+            return;
+        }
+        fees[inferenceId] = 0;
+        bonds[account] += amount;
+
+        emit FeeReleased(inferenceId, account, amount);
+    }
+
+    /// @notice Verifies the ZK proof of a whole inference, from the input to the output data hash.
+    function verifyInferenceProof(
+        uint256 inferenceId,
+        bytes32 outputDataHash,
+        bytes calldata publicValues,
+        bytes calldata proofBytes
+    ) internal view {
+        // SP1 verification
+        ISP1Verifier(SP1_VERIFIER).verifyProof(AGGREGATE_PROGRAM_VKEY, publicValues, proofBytes);
+
+        // Verify the public commitments of the proof
+        AggregatePublicValues memory values = decodeAggregatePublicValues(publicValues);
+        Model memory model = models[inferences[inferenceId].modelId];
+
+        // Verify the proof is bound to this inference
+        require(values.context.chainId == block.chainid, "chain id does not match");
+        require(values.context.verifier == address(this), "model registry address does not match");
+        require(values.context.domain == PROOF_DOMAIN_INFERENCE, "proof domain does not match");
+        require(values.context.id == inferenceId, "inference id does not match");
+
+        // Verify the aggregated operator proofs
+        require(values.operatorVkey == OPERATOR_PROGRAM_VKEY_DIGEST, "operator program does not match");
+        require(values.merkleRoot == model.root, "merkle root does not match");
+        require(values.numOperators == model.numOperators, "number of operators does not match");
+        require(
+            values.firstOperator == 0 && values.lastOperator == model.numOperators - 1,
+            "proof does not cover all ONNX operators"
+        );
+
+        // Verify input and output data hashes
+        require(values.inputDataHash == inferences[inferenceId].inputDataHash, "input data hash does not match");
+        require(values.outputDataHash == outputDataHash, "output data hash does not match");
+    }
+
+    /// @notice Decodes the ABI-encoded public values of the aggregation SP1 program.
+    function decodeAggregatePublicValues(bytes calldata publicValues)
+        public
+        pure
+        returns (AggregatePublicValues memory values)
+    {
+        values = abi.decode(publicValues, (AggregatePublicValues));
+    }
 }
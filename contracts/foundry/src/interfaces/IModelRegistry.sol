@@ -39,6 +39,46 @@ struct Inference {
     bytes outputData;
     /// @notice Output data hash.
     bytes32 outputDataHash;
+    /// @notice Flag indicating if the inference must be responded with a ZK proof of the whole inference.
+    bool zk;
+}
+
+/// @notice Domain of the proofs resolving a challenge of the FaultProof contract.
+uint8 constant PROOF_DOMAIN_CHALLENGE = 1;
+
+/// @notice Domain of the proofs responding to a full-ZK inference of the ModelRegistry contract.
+uint8 constant PROOF_DOMAIN_INFERENCE = 2;
+
+/// @notice Context an SP1 proof is bound to, so it cannot be replayed.
+struct ProofContext {
+    /// @notice Id of the chain the verifying contract is deployed on.
+    uint256 chainId;
+    /// @notice Address of the verifying contract (FaultProof for challenges, ModelRegistry for full-ZK inferences).
+    address verifier;
+    /// @notice Domain of the proof (PROOF_DOMAIN_CHALLENGE or PROOF_DOMAIN_INFERENCE).
+    uint8 domain;
+    /// @notice Id of the challenge or of the inference, depending on the domain.
+    uint256 id;
+}
+
+/// @notice Public values committed by the aggregation SP1 program (ABI-encoded).
+struct AggregatePublicValues {
+    /// @notice Context the aggregated proofs are bound to.
+    ProofContext context;
+    /// @notice Verification key digest of the operator SP1 program.
+    bytes32 operatorVkey;
+    /// @notice Merkle root of the model operators.
+    bytes32 merkleRoot;
+    /// @notice Number of ONNX operators of the model.
+    uint256 numOperators;
+    /// @notice Index of the first executed ONNX operator.
+    uint256 firstOperator;
+    /// @notice Index of the last executed ONNX operator.
+    uint256 lastOperator;
+    /// @notice Input data hash of the first operator execution.
+    bytes32 inputDataHash;
+    /// @notice Output data hash of the last operator execution.
+    bytes32 outputDataHash;
 }
 
 interface IModelRegistry {
@@ -47,4 +87,19 @@ interface IModelRegistry {
 
     /// @notice Returns an inference.
     function getInference(uint256 inferenceId) external view returns (Inference memory inference);
+
+    /// @notice Locks a part of the free bond of an account (challenger bonds).
+    function lockBond(address account, uint256 amount) external;
+
+    /// @notice Frees a locked bond amount to an account (bond payouts).
+    function unlockBond(address account, uint256 amount) external;
+
+    /// @notice Releases the escrowed fee of an inference to an account (responder or successful challenger).
+    function releaseFee(uint256 inferenceId, address account) external;
+
+    /// @notice Slashes the response bond of an inference, returns the slashed amount.
+    function slashResponseBond(uint256 inferenceId) external returns (uint256 amount);
+
+    /// @notice Frees the response bond of an inference to its responder.
+    function releaseResponseBond(uint256 inferenceId) external;
 }
@@ -0,0 +1,5 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.24;
+
+/// @dev Compiles the SP1 mock verifier with the contracts, so it can be deployed for local runs with mock proofs.
+import {SP1MockVerifier} from "sp1-contracts/src/SP1MockVerifier.sol";
@@ -3,12 +3,38 @@ pragma solidity ^0.8.24;
 
 import {Test, console} from "forge-std/Test.sol";
 import {ModelRegistry, Inference} from "../src/ModelRegistry.sol";
+import {
+    AggregatePublicValues,
+    PROOF_DOMAIN_CHALLENGE,
+    PROOF_DOMAIN_INFERENCE,
+    ProofContext
+} from "../src/interfaces/IModelRegistry.sol";
+import {SP1MockVerifier} from "sp1-contracts/src/SP1MockVerifier.sol";
 
 contract ModelRegistryTest is Test {
     ModelRegistry public modelRegistry;
 
+    bytes32 internal constant OPERATOR_VKEY_DIGEST = 0x2f4a1e0b6c7d8e9f00112233445566778899aabbccddeeff0011223344556677;
+    bytes32 internal constant MERKLE_ROOT = 0xcd316985c6f85acd9dc31a14fef75077a4fb3b9607236cc0fc8f6ac0434eefa8;
+    uint256 internal constant NUM_OPERATORS = 5;
+    bytes32 internal constant INPUT_DATA_HASH = 0xbe7c9f3b068911bc4e4f0a92253667a8661b250d2f82cc6f402efd7563939834;
+    bytes32 internal constant OUTPUT_DATA_HASH = 0x86f1c59c8509db81bd563c58bb57d487f9cb8f23a1bc4d2b4cf6614977fa94d7;
+
+    uint256 internal constant RESPONSE_BOND = 1 ether;
+    uint256 internal constant FEE = 0.1 ether;
+
+    receive() external payable {}
+
     function setUp() public {
-        modelRegistry = new ModelRegistry();
+        // mock verifier: proofs are empty, public values are still checked by ModelRegistry
+        modelRegistry = new ModelRegistry(
+            address(new SP1MockVerifier()),
+            bytes32(0x00a1b2c3d4e5f60718293a4b5c6d7e8f90a1b2c3d4e5f60718293a4b5c6d7e8f),
+            OPERATOR_VKEY_DIGEST,
+            RESPONSE_BOND,
+            address(this)
+        );
+        modelRegistry.depositBond{value: RESPONSE_BOND}();
     }
 
     function test_RegisterModel() public {
@@ -25,7 +51,8 @@ contract ModelRegistryTest is Test {
         uint256 inferenceId = modelRegistry.requestInference(
             modelId,
             "0xcd316986c6f85acd9dc31a14fef75077a4fb3b9607236cc0fc8f6ac0434eefa8",
-            bytes32(0xcd316985c6f85acd9dc31a14fef75077a4fb3b9607236cc0fc8f6ac0434eefa8)
+            bytes32(0xcd316985c6f85acd9dc31a14fef75077a4fb3b9607236cc0fc8f6ac0434eefa8),
+            false
         );
         assertEq(inferenceId, 0);
 
@@ -33,23 +60,201 @@ contract ModelRegistryTest is Test {
         assertEq(inference.modelId, modelId);
         assertEq(inference.inputData, "0xcd316986c6f85acd9dc31a14fef75077a4fb3b9607236cc0fc8f6ac0434eefa8");
         assertEq(inference.done, false);
+        assertEq(inference.zk, false);
+    }
+
+    function test_RequestInferenceFee() public {
+        uint256 inferenceId = modelRegistry.requestInference{value: FEE}(0, hex"00", INPUT_DATA_HASH, false);
+        assertEq(modelRegistry.fees(inferenceId), FEE);
+        assertEq(address(modelRegistry).balance, RESPONSE_BOND + FEE);
+
+        // the fee of an optimistic inference stays escrowed until the challenge window expires
+        modelRegistry.respondInference(inferenceId, hex"00", OUTPUT_DATA_HASH, "", "");
+        assertEq(modelRegistry.fees(inferenceId), FEE);
+        assertEq(modelRegistry.bonds(address(this)), 0);
     }
 
     function test_RespondInference() public {
         uint256 inferenceId = 0;
         bytes memory outputData = "0xcd316986c6f85acd9dc31a14fef75077a4fb3b9607236cc0fc8f6ac0434eefa8";
         bool success = modelRegistry.respondInference(
-            inferenceId, outputData, bytes32(0xcd316985c6f85acd9dc31a14fef75077a4fb3b9607236cc0fc8f6ac0434eefa8)
+            inferenceId, outputData, bytes32(0xcd316985c6f85acd9dc31a14fef75077a4fb3b9607236cc0fc8f6ac0434eefa8), "", ""
         );
         assertEq(success, true);
 
         Inference memory inference = modelRegistry.getInference(inferenceId);
         assertEq(inference.outputData, "0xcd316986c6f85acd9dc31a14fef75077a4fb3b9607236cc0fc8f6ac0434eefa8");
         assertEq(inference.done, true);
+        assertEq(modelRegistry.bonds(address(this)), 0);
+        assertEq(modelRegistry.responseBonds(inferenceId), RESPONSE_BOND);
 
         success = modelRegistry.respondInference(
-            inferenceId, outputData, bytes32(0xcd316985c6f85acd9dc31a14fef75077a4fb3b9607236cc0fc8f6ac0434eefa8)
+            inferenceId, outputData, bytes32(0xcd316985c6f85acd9dc31a14fef75077a4fb3b9607236cc0fc8f6ac0434eefa8), "", ""
         );
         assertEq(success, false);
     }
+
+    function test_RespondInferenceInsufficientBond() public {
+        vm.prank(address(0xbeef));
+        vm.expectRevert("insufficient bond");
+        modelRegistry.respondInference(0, hex"00", OUTPUT_DATA_HASH, "", "");
+    }
+
+    function test_WithdrawBond() public {
+        uint256 balance = address(this).balance;
+        modelRegistry.withdrawBond(RESPONSE_BOND);
+        assertEq(modelRegistry.bonds(address(this)), 0);
+        assertEq(address(this).balance, balance + RESPONSE_BOND);
+
+        vm.expectRevert("insufficient bond");
+        modelRegistry.withdrawBond(1);
+    }
+
+    function test_SettleBondOnlyFaultProof() public {
+        vm.expectRevert("only fault proof");
+        modelRegistry.unlockBond(address(this), RESPONSE_BOND);
+
+        vm.expectRevert("only fault proof");
+        modelRegistry.releaseFee(0, address(this));
+
+        vm.prank(address(0xbeef));
+        vm.expectRevert("only owner");
+        modelRegistry.setFaultProof(address(0xfa17));
+
+        modelRegistry.setFaultProof(address(0xfa17));
+        vm.expectRevert("fault proof already set");
+        modelRegistry.setFaultProof(address(this));
+    }
+
+    function test_RespondZkInference() public {
+        uint256 inferenceId = _requestZkInference();
+
+        bool success = modelRegistry.respondInference(
+            inferenceId, hex"00", OUTPUT_DATA_HASH, _publicValues(_context(inferenceId), 0, 4, OUTPUT_DATA_HASH), ""
+        );
+        assertEq(success, true);
+
+        Inference memory inference = modelRegistry.getInference(inferenceId);
+        assertEq(inference.zk, true);
+        assertEq(inference.done, true);
+        assertEq(inference.outputDataHash, OUTPUT_DATA_HASH);
+        assertEq(modelRegistry.responseBonds(inferenceId), 0);
+        // the fee is paid immediately
+        assertEq(modelRegistry.fees(inferenceId), 0);
+        assertEq(modelRegistry.bonds(address(this)), RESPONSE_BOND + FEE);
+    }
+
+    function test_RespondZkInferenceWrongOutput() public {
+        uint256 inferenceId = _requestZkInference();
+
+        vm.expectRevert("output data hash does not match");
+        modelRegistry.respondInference(
+            inferenceId,
+            hex"00",
+            bytes32(0x86e1c59c8509db81bd563c58bb57d487f9cb8f23a1bc4d2b4cf6614977fa94d7),
+            _publicValues(_context(inferenceId), 0, 4, OUTPUT_DATA_HASH),
+            ""
+        );
+    }
+
+    // Proof of a part of the model only.
+    function test_RespondZkInferencePartialProof() public {
+        uint256 inferenceId = _requestZkInference();
+
+        vm.expectRevert("proof does not cover all ONNX operators");
+        modelRegistry.respondInference(
+            inferenceId, hex"00", OUTPUT_DATA_HASH, _publicValues(_context(inferenceId), 1, 4, OUTPUT_DATA_HASH), ""
+        );
+    }
+
+    // Proof of a challenge with the same id as the inference (another domain).
+    function test_RespondZkInferenceWrongDomain() public {
+        uint256 inferenceId = _requestZkInference();
+
+        vm.expectRevert("proof domain does not match");
+        modelRegistry.respondInference(
+            inferenceId,
+            hex"00",
+            OUTPUT_DATA_HASH,
+            _publicValues(
+                ProofContext(block.chainid, address(modelRegistry), PROOF_DOMAIN_CHALLENGE, inferenceId),
+                0,
+                4,
+                OUTPUT_DATA_HASH
+            ),
+            ""
+        );
+    }
+
+    // Proof of all the operators of a forged Merkle tree shape.
+    function test_RespondZkInferenceWrongNumOperators() public {
+        uint256 inferenceId = _requestZkInference();
+
+        vm.expectRevert("number of operators does not match");
+        modelRegistry.respondInference(
+            inferenceId,
+            hex"00",
+            OUTPUT_DATA_HASH,
+            _publicValues(_context(inferenceId), NUM_OPERATORS + 1, 0, NUM_OPERATORS, OUTPUT_DATA_HASH),
+            ""
+        );
+    }
+
+    // Proof generated for another inference with the same input data.
+    function test_RespondZkInferenceReplayedProof() public {
+        uint256 inferenceId = _requestZkInference();
+
+        vm.expectRevert("inference id does not match");
+        modelRegistry.respondInference(
+            inferenceId, hex"00", OUTPUT_DATA_HASH, _publicValues(_context(inferenceId + 1), 0, 4, OUTPUT_DATA_HASH), ""
+        );
+    }
+
+    function test_RespondZkInferenceWithoutProof() public {
+        uint256 inferenceId = _requestZkInference();
+
+        vm.expectRevert();
+        modelRegistry.respondInference(inferenceId, hex"00", OUTPUT_DATA_HASH, "", "");
+    }
+
+    function _requestZkInference() internal returns (uint256 inferenceId) {
+        modelRegistry.registerModel(
+            "ipfs://QmTzQ1dz4N7UwW7EeLNkFvn7sPjscV8RjyC3K7yNZF2egM", MERKLE_ROOT, NUM_OPERATORS
+        );
+        inferenceId = modelRegistry.requestInference{value: FEE}(0, hex"00", INPUT_DATA_HASH, true);
+    }
+
+    function _context(uint256 inferenceId) internal view returns (ProofContext memory) {
+        return ProofContext(block.chainid, address(modelRegistry), PROOF_DOMAIN_INFERENCE, inferenceId);
+    }
+
+    function _publicValues(
+        ProofContext memory context,
+        uint256 firstOperator,
+        uint256 lastOperator,
+        bytes32 outputDataHash
+    ) internal pure returns (bytes memory) {
+        return _publicValues(context, NUM_OPERATORS, firstOperator, lastOperator, outputDataHash);
+    }
+
+    function _publicValues(
+        ProofContext memory context,
+        uint256 numOperators,
+        uint256 firstOperator,
+        uint256 lastOperator,
+        bytes32 outputDataHash
+    ) internal pure returns (bytes memory) {
+        return abi.encode(
+            AggregatePublicValues(
+                context,
+                OPERATOR_VKEY_DIGEST,
+                MERKLE_ROOT,
+                numOperators,
+                firstOperator,
+                lastOperator,
+                INPUT_DATA_HASH,
+                outputDataHash
+            )
+        );
+    }
 }
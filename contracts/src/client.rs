@@ -0,0 +1,275 @@
+use alloy::{
+    contract::{CallBuilder, CallDecoder},
+    primitives::{Address, Bytes, B256, U256},
+    providers::Provider,
+    rpc::types::TransactionReceipt,
+    sol_types::SolEvent,
+};
+use std::time::Duration;
+
+use crate::{
+    FaultProof::{self, FaultProofInstance},
+    ModelRegistry::{self, Inference, ModelRegistryInstance},
+};
+
+/// `ChallengeActor.RESPONDER` of the FaultProof contract (`lastActor` of a challenge).
+pub const CHALLENGE_ACTOR_RESPONDER: u8 = 0;
+
+/// `ChallengeActor.CHALLENGER` of the FaultProof contract (`lastActor` of a challenge).
+pub const CHALLENGE_ACTOR_CHALLENGER: u8 = 1;
+
+/// Default interval between two polls of [`ZkopmlClient::wait_for_result`].
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// High-level client of the zk-OPML contracts.
+///
+/// Transactions wait for the configured number of confirmations, and the ids of new models,
+/// inferences and challenges are read from the events of the transaction receipt.
+#[derive(Debug, Clone)]
+pub struct ZkopmlClient<P> {
+    provider: P,
+    model_registry: Address,
+    fault_proof: Option<Address>,
+    confirmations: u64,
+}
+
+impl<P: Provider + Clone> ZkopmlClient<P> {
+    pub fn new(provider: P, model_registry: Address) -> Self {
+        Self {
+            provider,
+            model_registry,
+            fault_proof: None,
+            confirmations: 1,
+        }
+    }
+
+    /// Address of the FaultProof contract, required for challenges.
+    pub fn with_fault_proof(mut self, fault_proof: Address) -> Self {
+        self.fault_proof = Some(fault_proof);
+        self
+    }
+
+    /// Number of confirmations to wait for after each transaction (defaults to 1).
+    pub fn with_confirmations(mut self, confirmations: u64) -> Self {
+        self.confirmations = confirmations;
+        self
+    }
+
+    pub fn provider(&self) -> &P {
+        &self.provider
+    }
+
+    pub fn model_registry(&self) -> ModelRegistryInstance<P> {
+        ModelRegistry::new(self.model_registry, self.provider.clone())
+    }
+
+    pub fn fault_proof(&self) -> anyhow::Result<FaultProofInstance<P>> {
+        let fault_proof = self
+            .fault_proof
+            .ok_or_else(|| anyhow::anyhow!("Missing FaultProof contract address"))?;
+        Ok(FaultProof::new(fault_proof, self.provider.clone()))
+    }
+
+    /// Registers a model and returns its id.
+    pub async fn register_model(
+        &self,
+        uri: String,
+        root: B256,
+        num_operators: U256,
+    ) -> anyhow::Result<U256> {
+        let model_registry = self.model_registry();
+        let receipt = self
+            .send(model_registry.registerModel(uri, root, num_operators))
+            .await?;
+        let event: ModelRegistry::ModelRegistered = event(&receipt, self.model_registry)?;
+
+        Ok(event.id)
+    }
+
+    /// Requests an inference, escrowing its fee, and returns its id.
+    pub async fn request_inference(
+        &self,
+        model_id: U256,
+        input_data: Bytes,
+        input_data_hash: B256,
+        zk: bool,
+        fee: U256,
+    ) -> anyhow::Result<U256> {
+        let model_registry = self.model_registry();
+        let receipt = self
+            .send(
+                model_registry
+                    .requestInference(model_id, input_data, input_data_hash, zk)
+                    .value(fee),
+            )
+            .await?;
+        let event: ModelRegistry::InferenceRequested = event(&receipt, self.model_registry)?;
+
+        Ok(event.inferenceId)
+    }
+
+    /// Responds to an inference and returns whether the response was accepted (the inference was
+    /// not responded yet).
+    ///
+    /// The public values and the proof are only verified for full-ZK inferences, optimistic
+    /// inferences can pass empty bytes.
+    pub async fn respond(
+        &self,
+        inference_id: U256,
+        output_data: Bytes,
+        output_data_hash: B256,
+        public_values: Bytes,
+        proof_bytes: Bytes,
+    ) -> anyhow::Result<bool> {
+        let model_registry = self.model_registry();
+        let receipt = self
+            .send(model_registry.respondInference(
+                inference_id,
+                output_data,
+                output_data_hash,
+                public_values,
+                proof_bytes,
+            ))
+            .await?;
+        // The call does not revert if the inference was already responded
+        Ok(event::<ModelRegistry::InferenceResponded>(&receipt, self.model_registry).is_ok())
+    }
+
+    /// Challenges the response of an inference and returns the challenge id.
+    pub async fn create_challenge(&self, inference_id: U256) -> anyhow::Result<U256> {
+        let fault_proof = self.fault_proof()?;
+        let receipt = self.send(fault_proof.createChallenge(inference_id)).await?;
+        let event: FaultProof::ChallengeCreated = event(&receipt, *fault_proof.address())?;
+
+        Ok(event.challengeId)
+    }
+
+    /// Bisection state of a challenge.
+    pub async fn challenge(&self, challenge_id: U256) -> anyhow::Result<FaultProof::Challenge> {
+        Ok(self
+            .fault_proof()?
+            .getChallenge(challenge_id)
+            .call()
+            .await?)
+    }
+
+    /// Fee still escrowed by an inference (paid to the responder after its challenge window, or to
+    /// a successful challenger).
+    pub async fn fee(&self, inference_id: U256) -> anyhow::Result<U256> {
+        Ok(self.model_registry().fees(inference_id).call().await?)
+    }
+
+    /// Number of unresolved challenges of an inference.
+    pub async fn open_challenges(&self, inference_id: U256) -> anyhow::Result<U256> {
+        Ok(self
+            .fault_proof()?
+            .openChallenges(inference_id)
+            .call()
+            .await?)
+    }
+
+    /// Free (withdrawable) bond of an account in the ModelRegistry, including the released fees.
+    pub async fn bond(&self, account: Address) -> anyhow::Result<U256> {
+        Ok(self.model_registry().bonds(account).call().await?)
+    }
+
+    /// Bond locked by each optimistic inference response.
+    pub async fn response_bond(&self) -> anyhow::Result<U256> {
+        Ok(self.model_registry().RESPONSE_BOND().call().await?)
+    }
+
+    /// Response bond still locked by an inference (released after its challenge window, or
+    /// slashed by a successful challenge).
+    pub async fn locked_response_bond(&self, inference_id: U256) -> anyhow::Result<U256> {
+        Ok(self
+            .model_registry()
+            .responseBonds(inference_id)
+            .call()
+            .await?)
+    }
+
+    /// Bond locked by each challenge until it is resolved.
+    pub async fn challenge_bond(&self) -> anyhow::Result<U256> {
+        Ok(self.fault_proof()?.challengeBond().call().await?)
+    }
+
+    /// Deposits a bond for the sender.
+    pub async fn deposit_bond(&self, amount: U256) -> anyhow::Result<()> {
+        let model_registry = self.model_registry();
+        self.send(model_registry.depositBond().value(amount))
+            .await?;
+
+        Ok(())
+    }
+
+    /// Withdraws a part of the free bond of the sender.
+    pub async fn withdraw_bond(&self, amount: U256) -> anyhow::Result<()> {
+        let model_registry = self.model_registry();
+        self.send(model_registry.withdrawBond(amount)).await?;
+
+        Ok(())
+    }
+
+    /// Releases the response bond and the fee of an inference to its responder, once its challenge
+    /// window expired and its challenges are resolved.
+    pub async fn release_response_bond(&self, inference_id: U256) -> anyhow::Result<()> {
+        let fault_proof = self.fault_proof()?;
+        self.send(fault_proof.releaseResponseBond(inference_id))
+            .await?;
+
+        Ok(())
+    }
+
+    /// Waits until the inference is responded and returns it.
+    pub async fn wait_for_result(
+        &self,
+        inference_id: U256,
+        poll_interval: Duration,
+    ) -> anyhow::Result<Inference> {
+        let model_registry = self.model_registry();
+        loop {
+            let inference = model_registry.getInference(inference_id).call().await?;
+            if inference.done {
+                return Ok(inference);
+            }
+            tokio::time::sleep(poll_interval).await;
+        }
+    }
+
+    /// Sends the transaction and waits for its confirmations, failing if it reverted.
+    async fn send<D: CallDecoder>(
+        &self,
+        call: CallBuilder<&P, D>,
+    ) -> anyhow::Result<TransactionReceipt> {
+        let receipt = call
+            .send()
+            .await?
+            .with_required_confirmations(self.confirmations)
+            .get_receipt()
+            .await?;
+        anyhow::ensure!(
+            receipt.status(),
+            "Transaction {} reverted",
+            receipt.transaction_hash
+        );
+
+        Ok(receipt)
+    }
+}
+
+/// Returns the first event of type `E` emitted by `address` in the receipt.
+pub fn event<E: SolEvent>(receipt: &TransactionReceipt, address: Address) -> anyhow::Result<E> {
+    receipt
+        .logs()
+        .iter()
+        .filter(|log| log.address() == address)
+        .find_map(|log| log.log_decode::<E>().ok())
+        .map(|log| log.inner.data)
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Missing {} event in transaction {}",
+                E::SIGNATURE,
+                receipt.transaction_hash
+            )
+        })
+}
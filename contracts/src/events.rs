@@ -0,0 +1,289 @@
+use alloy::{
+    primitives::{Address, B256},
+    providers::Provider,
+    pubsub::SubscriptionStream,
+    rpc::types::{Filter, Header, Log},
+    sol_types::SolEventInterface,
+};
+use futures_util::StreamExt;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+use crate::{FaultProof::FaultProofEvents, ModelRegistry::ModelRegistryEvents};
+
+/// Maximum number of blocks requested in a single `eth_getLogs` call.
+const MAX_BLOCK_RANGE: u64 = 10_000;
+
+/// Default interval between two polls of the chain head when block subscriptions are not
+/// supported by the transport.
+pub const DEFAULT_BLOCK_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Default maximum backoff between two attempts of the stream after a failure of the node.
+pub const DEFAULT_MAX_RETRY_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Backoff after the first failure of the node, doubled on each consecutive failure.
+const INITIAL_RETRY_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Events emitted by the ModelRegistry and FaultProof contracts.
+#[derive(Debug, Clone)]
+pub enum ZkopmlEvent {
+    ModelRegistry(ModelRegistryEvents),
+    FaultProof(FaultProofEvents),
+}
+
+impl ZkopmlEvent {
+    /// Decodes a log of the zk-OPML contracts, returns `None` for unknown events.
+    pub fn decode(log: &Log) -> Option<Self> {
+        let (topics, data) = (log.topics(), &log.data().data);
+        if let Ok(event) = ModelRegistryEvents::decode_raw_log(topics, data) {
+            return Some(Self::ModelRegistry(event));
+        }
+        if let Ok(event) = FaultProofEvents::decode_raw_log(topics, data) {
+            return Some(Self::FaultProof(event));
+        }
+
+        None
+    }
+}
+
+/// A decoded event together with its position on chain.
+#[derive(Debug, Clone)]
+pub struct EventLog {
+    pub event: ZkopmlEvent,
+    pub address: Address,
+    pub block_number: u64,
+    pub transaction_hash: B256,
+    pub log_index: u64,
+}
+
+/// Stream of the events of the zk-OPML contracts, see [`EventStreamBuilder`].
+#[derive(Debug)]
+pub struct EventStream {
+    receiver: mpsc::Receiver<anyhow::Result<EventLog>>,
+}
+
+impl EventStream {
+    /// Returns the next event, or `None` once the stream stopped after an error (only when
+    /// [`EventStreamBuilder::max_retries`] is set, failures of the node are retried otherwise).
+    pub async fn next(&mut self) -> Option<anyhow::Result<EventLog>> {
+        self.receiver.recv().await
+    }
+}
+
+/// Builder of an [`EventStream`].
+///
+/// The stream first backfills the events from `from_block` with `eth_getLogs`, then follows the
+/// new blocks (through a block subscription, or by polling if the transport does not support
+/// subscriptions, e.g. HTTP). Events are only emitted once their block has the configured number of
+/// confirmations, in chain order, and each event is emitted once: a block range is fetched
+/// completely before any of its events is emitted, and the next range only starts once it was
+/// fully emitted, so the range of a failed fetch is fetched again without duplicates.
+///
+/// Logs are always fetched by block range rather than with node-side filters, so that a failure of
+/// the node (dropped WebSocket, restart) is recovered from by resubscribing and fetching again
+/// from the last processed block, with exponential backoff between the attempts.
+#[derive(Debug, Clone)]
+pub struct EventStreamBuilder<P> {
+    provider: P,
+    addresses: Vec<Address>,
+    from_block: Option<u64>,
+    confirmations: u64,
+    poll_interval: Duration,
+    max_retries: Option<u32>,
+    max_retry_backoff: Duration,
+}
+
+impl<P: Provider + Clone + 'static> EventStreamBuilder<P> {
+    pub fn new(provider: P, addresses: Vec<Address>) -> Self {
+        Self {
+            provider,
+            addresses,
+            from_block: None,
+            confirmations: 0,
+            poll_interval: DEFAULT_BLOCK_POLL_INTERVAL,
+            max_retries: None,
+            max_retry_backoff: DEFAULT_MAX_RETRY_BACKOFF,
+        }
+    }
+
+    /// First block to backfill the events from (defaults to the first block not yet confirmed).
+    pub fn from_block(mut self, from_block: Option<u64>) -> Self {
+        self.from_block = from_block;
+        self
+    }
+
+    /// Number of blocks to wait on top of the block of an event before emitting it (defaults to
+    /// 0).
+    pub fn confirmations(mut self, confirmations: u64) -> Self {
+        self.confirmations = confirmations;
+        self
+    }
+
+    /// Interval between two polls of the chain head, used when block subscriptions are not
+    /// supported.
+    pub fn poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Maximum number of consecutive failures (e.g. the node is unreachable) before the stream
+    /// stops with the last error (defaults to retrying forever).
+    pub fn max_retries(mut self, max_retries: Option<u32>) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Maximum backoff between two attempts after a failure, doubled from 1 second on each
+    /// consecutive failure (defaults to 60 seconds).
+    pub fn max_retry_backoff(mut self, max_retry_backoff: Duration) -> Self {
+        self.max_retry_backoff = max_retry_backoff;
+        self
+    }
+
+    /// Starts the stream in the background.
+    pub fn start(self) -> EventStream {
+        let (sender, receiver) = mpsc::channel(1024);
+        tokio::spawn(async move {
+            if let Err(error) = self.run(&sender).await {
+                let _ = sender.send(Err(error)).await;
+            }
+        });
+
+        EventStream { receiver }
+    }
+
+    /// Follows the chain, restarting from the last processed block after a failure of the node
+    /// (dropped connection, restart, transient RPC error) until the stream is dropped.
+    async fn run(self, sender: &mpsc::Sender<anyhow::Result<EventLog>>) -> anyhow::Result<()> {
+        let mut next_block = self.from_block;
+        let mut blocks = None;
+        let mut failures = 0;
+        let mut progress = next_block;
+
+        loop {
+            match self.follow(&mut next_block, &mut blocks, sender).await {
+                Ok(()) => return Ok(()),
+                Err(error) => {
+                    // Only back off on consecutive failures without progress
+                    if next_block != progress {
+                        progress = next_block;
+                        failures = 0;
+                    }
+                    failures += 1;
+                    if self
+                        .max_retries
+                        .is_some_and(|max_retries| failures > max_retries)
+                    {
+                        return Err(error);
+                    }
+                    let backoff = INITIAL_RETRY_BACKOFF
+                        .saturating_mul(1 << (failures - 1).min(16))
+                        .min(self.max_retry_backoff);
+                    tracing::warn!(
+                        "Event stream failed (attempt {}), retrying in {:?}: {:#}",
+                        failures,
+                        backoff,
+                        error
+                    );
+                    blocks = None;
+                    tokio::time::sleep(backoff).await;
+                    if sender.is_closed() {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Emits the confirmed events and waits for new blocks, until the stream is dropped (returns
+    /// `Ok`) or the node fails (returns the error, `next_block` is the first block not processed).
+    async fn follow(
+        &self,
+        next_block: &mut Option<u64>,
+        blocks: &mut Option<SubscriptionStream<Header>>,
+        sender: &mpsc::Sender<anyhow::Result<EventLog>>,
+    ) -> anyhow::Result<()> {
+        if next_block.is_none() {
+            let head = self.provider.get_block_number().await?;
+            *next_block = Some(head.saturating_sub(self.confirmations) + 1);
+        }
+        // Subscribe to the new blocks on pub-sub transports, HTTP transports poll the chain head
+        if blocks.is_none() && self.provider.client().pubsub_frontend().is_some() {
+            *blocks = Some(self.provider.subscribe_blocks().await?.into_stream());
+        }
+
+        loop {
+            let head = self.provider.get_block_number().await?;
+            let confirmed = head.saturating_sub(self.confirmations);
+            while let Some(from_block) = next_block.filter(|from_block| *from_block <= confirmed) {
+                let to_block = confirmed.min(from_block + MAX_BLOCK_RANGE - 1);
+                let events =
+                    fetch_events(&self.provider, &self.addresses, from_block, to_block).await?;
+
+                for event_log in events {
+                    if sender.send(Ok(event_log)).await.is_err() {
+                        // The stream was dropped
+                        return Ok(());
+                    }
+                }
+                // The range is fully emitted, it is not fetched again
+                *next_block = Some(to_block + 1);
+            }
+
+            // Wait for the next block
+            match blocks.as_mut() {
+                Some(stream) => {
+                    if stream.next().await.is_none() {
+                        anyhow::bail!("The block subscription ended");
+                    }
+                }
+                None => tokio::time::sleep(self.poll_interval).await,
+            }
+            if sender.is_closed() {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Fetches the events of the zk-OPML contracts between two blocks (inclusive) with `eth_getLogs`,
+/// in chain order.
+pub async fn fetch_events<P: Provider>(
+    provider: &P,
+    addresses: &[Address],
+    from_block: u64,
+    to_block: u64,
+) -> anyhow::Result<Vec<EventLog>> {
+    let mut events = Vec::new();
+    let mut next_block = from_block;
+    while next_block <= to_block {
+        let chunk_end = to_block.min(next_block + MAX_BLOCK_RANGE - 1);
+        let filter = Filter::new()
+            .address(addresses.to_vec())
+            .from_block(next_block)
+            .to_block(chunk_end);
+        let mut logs = provider.get_logs(&filter).await?;
+        logs.sort_by_key(|log| (log.block_number, log.log_index));
+
+        for log in logs {
+            let (Some(block_number), Some(transaction_hash), Some(log_index)) =
+                (log.block_number, log.transaction_hash, log.log_index)
+            else {
+                continue;
+            };
+            let Some(event) = ZkopmlEvent::decode(&log) else {
+                continue;
+            };
+            events.push(EventLog {
+                event,
+                address: log.address(),
+                block_number,
+                transaction_hash,
+                log_index,
+            });
+        }
+        next_block = chunk_end + 1;
+    }
+
+    Ok(events)
+}
@@ -1,13 +1,25 @@
 use alloy::sol;
 
+pub mod client;
+pub mod events;
+
+pub use client::{ZkopmlClient, CHALLENGE_ACTOR_CHALLENGER, CHALLENGE_ACTOR_RESPONDER};
+pub use events::{fetch_events, EventLog, EventStream, EventStreamBuilder, ZkopmlEvent};
+
 sol!(
-    #[sol(rpc)]
+    #[sol(rpc, all_derives)]
     ModelRegistry,
-    "foundry/out/ModelRegistry.sol/ModelRegistry.json"
+    "artifacts/ModelRegistry.json"
 );
 
 sol!(
-    #[sol(rpc)]
+    #[sol(rpc, all_derives)]
     FaultProof,
-    "foundry/out/FaultProof.sol/FaultProof.json"
+    "artifacts/FaultProof.json"
+);
+
+sol!(
+    #[sol(rpc)]
+    SP1MockVerifier,
+    "artifacts/SP1MockVerifier.json"
 );
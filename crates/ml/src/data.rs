@@ -1,8 +1,11 @@
 use candle_core::Tensor;
+#[cfg(feature = "host")]
 use serde_json::Value;
+use std::collections::HashMap;
+#[cfg(feature = "host")]
 use std::collections::VecDeque;
 
-use crate::utils::hash_buffer;
+use crate::{encoding::encode_state, utils::hash_buffer};
 
 pub fn tensor_hash(tensor: &Tensor) -> [u8; 32] {
     // TODO: future work how to more efficiently hash tensors
@@ -12,6 +15,12 @@ pub fn tensor_hash(tensor: &Tensor) -> [u8; 32] {
     hash_buffer(&buffer)
 }
 
+/// Hash of an inference state, given the hashes of all of its tensors.
+pub fn state_hash(hashes: &HashMap<String, [u8; 32]>) -> [u8; 32] {
+    hash_buffer(&encode_state(hashes))
+}
+
+#[cfg(feature = "host")]
 pub fn extract_input_data(json_str: &str) -> anyhow::Result<Vec<f64>> {
     let json: Value = serde_json::from_str(json_str)?;
 
@@ -25,6 +34,7 @@ pub fn extract_input_data(json_str: &str) -> anyhow::Result<Vec<f64>> {
     Ok(result)
 }
 
+#[cfg(feature = "host")]
 fn extract_numbers_recursive(value: &Value, result: &mut Vec<f64>) -> anyhow::Result<()> {
     match value {
         Value::Number(n) => {
@@ -46,6 +56,7 @@ fn extract_numbers_recursive(value: &Value, result: &mut Vec<f64>) -> anyhow::Re
     Ok(())
 }
 
+#[cfg(feature = "host")]
 pub fn extract_input_data_iterative(json_str: &str) -> anyhow::Result<Vec<f64>> {
     let json: Value = serde_json::from_str(json_str)?;
 
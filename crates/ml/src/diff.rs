@@ -0,0 +1,300 @@
+use crate::{
+    encoding::encode_attributes,
+    merkle::{MerkleTreeHash, ModelMerkleTree},
+    onnx::Model,
+    utils::{NodeHashInputs, node_hash_inputs},
+};
+use anyhow::Context;
+use candle_onnx::onnx::NodeProto;
+use std::collections::{HashMap, HashSet};
+
+/// Reference to an ONNX operator of one of the compared models.
+#[derive(Debug, Clone)]
+pub struct OperatorRef {
+    /// Index of the operator (Merkle leaf index).
+    pub index: usize,
+    /// Name of the operator, or `<op_type>#<occurrence>` for unnamed operators.
+    pub key: String,
+    /// ONNX operator type.
+    pub op_type: String,
+}
+
+/// Operator present in both models whose leaf hash changed.
+#[derive(Debug, Clone)]
+pub struct ModifiedOperator {
+    pub old: OperatorRef,
+    pub new: OperatorRef,
+    /// The operator attributes differ.
+    pub attributes_changed: bool,
+    /// The operator inputs or outputs were rewired or renamed (or its domain changed).
+    pub wiring_changed: bool,
+    /// Names of the graph initializers whose tensors differ, were added or were removed.
+    pub initializers_changed: Vec<String>,
+}
+
+/// Result of a leaf-by-leaf comparison of two models.
+#[derive(Debug, Clone)]
+pub struct ModelDiff {
+    pub old_root: MerkleTreeHash,
+    pub new_root: MerkleTreeHash,
+    pub added: Vec<OperatorRef>,
+    pub removed: Vec<OperatorRef>,
+    pub modified: Vec<ModifiedOperator>,
+    /// Operators with identical leaf hashes (possibly at a different index).
+    pub unchanged: usize,
+    /// The graph interface (header leaf) differs.
+    pub interface_changed: bool,
+}
+
+impl ModelDiff {
+    /// Compares two models operator by operator.
+    ///
+    /// Operators are aligned by op type and name. Unnamed operators are aligned by their
+    /// occurrence among the operators of the same type.
+    pub fn new(old: &Model, new: &Model) -> anyhow::Result<Self> {
+        let old_graph = old.graph().context("The old model has no graph")?;
+        let new_graph = new.graph().context("The new model has no graph")?;
+        let old_tree = ModelMerkleTree::new(old_graph.node.clone(), old_graph.clone());
+        let new_tree = ModelMerkleTree::new(new_graph.node.clone(), new_graph.clone());
+
+        let old_refs = operator_refs(&old_graph.node);
+        let new_refs = operator_refs(&new_graph.node);
+        let new_by_key: HashMap<(&str, &str), &OperatorRef> = new_refs
+            .iter()
+            .map(|r| ((r.op_type.as_str(), r.key.as_str()), r))
+            .collect();
+
+        let mut matched = HashSet::new();
+        let mut removed = Vec::new();
+        let mut modified = Vec::new();
+        let mut unchanged = 0;
+
+        for old_ref in old_refs.iter() {
+            let Some(new_ref) = new_by_key.get(&(old_ref.op_type.as_str(), old_ref.key.as_str()))
+            else {
+                removed.push(old_ref.clone());
+                continue;
+            };
+            matched.insert(new_ref.index);
+
+            let old_leaf = old_tree.leaves_hashes(vec![old_ref.index])[0];
+            let new_leaf = new_tree.leaves_hashes(vec![new_ref.index])[0];
+            if old_leaf == new_leaf {
+                unchanged += 1;
+                continue;
+            }
+
+            let old_node = &old_graph.node[old_ref.index];
+            let new_node = &new_graph.node[new_ref.index];
+            let old_inputs = node_hash_inputs(old_node, &old_graph);
+            let new_inputs = node_hash_inputs(new_node, &new_graph);
+            modified.push(ModifiedOperator {
+                old: old_ref.clone(),
+                new: (*new_ref).clone(),
+                attributes_changed: encode_attributes(old_node) != encode_attributes(new_node),
+                wiring_changed: old_node.input != new_node.input
+                    || old_node.output != new_node.output
+                    || old_node.domain != new_node.domain,
+                initializers_changed: initializers_changed(&old_inputs, &new_inputs),
+            });
+        }
+
+        let added = new_refs
+            .into_iter()
+            .filter(|r| !matched.contains(&r.index))
+            .collect();
+
+        let old_header = old_tree.leaves_hashes(vec![old_tree.header_index()])[0];
+        let new_header = new_tree.leaves_hashes(vec![new_tree.header_index()])[0];
+
+        Ok(Self {
+            old_root: old_tree.root(),
+            new_root: new_tree.root(),
+            added,
+            removed,
+            modified,
+            unchanged,
+            interface_changed: old_header != new_header,
+        })
+    }
+
+    /// Whether the model commitment changed, i.e. the new model needs a new registration.
+    pub fn root_changed(&self) -> bool {
+        self.old_root != self.new_root
+    }
+}
+
+fn operator_refs(nodes: &[NodeProto]) -> Vec<OperatorRef> {
+    let mut occurrences: HashMap<&str, usize> = HashMap::new();
+
+    nodes
+        .iter()
+        .enumerate()
+        .map(|(index, node)| {
+            let occurrence = occurrences.entry(node.op_type.as_str()).or_insert(0);
+            let key = if node.name.is_empty() {
+                format!("{}#{}", node.op_type, occurrence)
+            } else {
+                node.name.clone()
+            };
+            *occurrence += 1;
+
+            OperatorRef {
+                index,
+                key,
+                op_type: node.op_type.clone(),
+            }
+        })
+        .collect()
+}
+
+fn initializers_changed(old: &NodeHashInputs, new: &NodeHashInputs) -> Vec<String> {
+    let old_hashes: HashMap<&String, &[u8; 32]> =
+        old.initializers.iter().map(|(n, h)| (n, h)).collect();
+    let new_hashes: HashMap<&String, &[u8; 32]> =
+        new.initializers.iter().map(|(n, h)| (n, h)).collect();
+
+    let mut names: Vec<String> = old_hashes
+        .keys()
+        .chain(new_hashes.keys())
+        .filter(|name| old_hashes.get(*name) != new_hashes.get(*name))
+        .map(|name| name.to_string())
+        .collect();
+    names.sort();
+    names.dedup();
+
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use candle_onnx::onnx::{
+        AttributeProto, GraphProto, ModelProto, TensorProto, ValueInfoProto,
+        attribute_proto::AttributeType, tensor_proto::DataType,
+    };
+
+    fn value_info(name: &str) -> ValueInfoProto {
+        ValueInfoProto {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn node(name: &str, op_type: &str, inputs: &[&str], outputs: &[&str]) -> NodeProto {
+        NodeProto {
+            name: name.to_string(),
+            op_type: op_type.to_string(),
+            input: inputs.iter().map(|input| input.to_string()).collect(),
+            output: outputs.iter().map(|output| output.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    /// `z = Relu(Gemm(x, w, alpha))`, with an unnamed Relu.
+    fn graph() -> GraphProto {
+        let mut gemm = node("gemm", "Gemm", &["x", "w"], &["y"]);
+        gemm.attribute.push(AttributeProto {
+            name: "alpha".to_string(),
+            r#type: AttributeType::Float as i32,
+            f: 1.0,
+            ..Default::default()
+        });
+
+        GraphProto {
+            node: vec![gemm, node("", "Relu", &["y"], &["z"])],
+            initializer: vec![TensorProto {
+                name: "w".to_string(),
+                data_type: DataType::Float as i32,
+                dims: vec![2],
+                float_data: vec![1.0, 2.0],
+                ..Default::default()
+            }],
+            input: vec![value_info("x")],
+            output: vec![value_info("z")],
+            ..Default::default()
+        }
+    }
+
+    fn model(graph: GraphProto) -> Model {
+        Model {
+            inner: ModelProto {
+                graph: Some(graph),
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn unchanged_model() {
+        let diff = ModelDiff::new(&model(graph()), &model(graph())).unwrap();
+
+        assert!(!diff.root_changed());
+        assert!(!diff.interface_changed);
+        assert!(diff.added.is_empty() && diff.removed.is_empty() && diff.modified.is_empty());
+        assert_eq!(diff.unchanged, 2);
+    }
+
+    #[test]
+    fn changed_attribute() {
+        let mut new_graph = graph();
+        new_graph.node[0].attribute[0].f = 0.5;
+        let diff = ModelDiff::new(&model(graph()), &model(new_graph)).unwrap();
+
+        assert!(diff.root_changed());
+        assert_eq!(diff.modified.len(), 1);
+        let modified = &diff.modified[0];
+        assert_eq!(modified.new.key, "gemm");
+        assert!(modified.attributes_changed);
+        assert!(!modified.wiring_changed);
+        assert!(modified.initializers_changed.is_empty());
+        assert_eq!(diff.unchanged, 1);
+    }
+
+    #[test]
+    fn changed_wiring() {
+        let mut new_graph = graph();
+        new_graph.node[0].output[0] = "y2".to_string();
+        new_graph.node[1].input[0] = "y2".to_string();
+        let diff = ModelDiff::new(&model(graph()), &model(new_graph)).unwrap();
+
+        assert_eq!(diff.modified.len(), 2);
+        for modified in diff.modified.iter() {
+            assert!(!modified.attributes_changed);
+            assert!(modified.wiring_changed);
+        }
+    }
+
+    #[test]
+    fn changed_initializer() {
+        let mut new_graph = graph();
+        new_graph.initializer[0].float_data = vec![1.0, 3.0];
+        let diff = ModelDiff::new(&model(graph()), &model(new_graph)).unwrap();
+
+        assert_eq!(diff.modified.len(), 1);
+        let modified = &diff.modified[0];
+        assert!(!modified.attributes_changed);
+        assert!(!modified.wiring_changed);
+        assert_eq!(modified.initializers_changed, vec!["w".to_string()]);
+    }
+
+    #[test]
+    fn added_and_removed_unnamed_operator() {
+        let mut new_graph = graph();
+        new_graph.node.push(node("", "Relu", &["z"], &["out"]));
+        new_graph.output = vec![value_info("out")];
+
+        let diff = ModelDiff::new(&model(graph()), &model(new_graph.clone())).unwrap();
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].key, "Relu#1");
+        assert_eq!(diff.added[0].index, 2);
+        assert!(diff.removed.is_empty());
+        assert!(diff.interface_changed);
+
+        let diff = ModelDiff::new(&model(new_graph), &model(graph())).unwrap();
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].key, "Relu#1");
+        assert!(diff.added.is_empty());
+        assert_eq!(diff.unchanged, 2);
+    }
+}
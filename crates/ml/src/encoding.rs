@@ -0,0 +1,410 @@
+//! Canonical binary encoding of ONNX operators, of the graph interface and of inference states.
+//!
+//! The encodings are what gets hashed into the Merkle leaves of a model and into the state
+//! hashes, so they must not depend on any serialization library. They are shared by the host and
+//! the zkVM guest.
+//!
+//! All integers are little-endian, lengths are `u32`, floats are encoded by their IEEE-754 bits,
+//! strings and byte strings are length-prefixed and lists are length-prefixed sequences.
+//!
+//! ```text
+//! node      = bytes(NODE_TAG) u32(NODE_VERSION) node_body
+//! node_body = str(op_type) str(domain) list(str input) list(str output) list(attribute)
+//! attribute = str(name) str(ref_attr_name) i32(type) f32(f) i64(i) bytes(s) option(tensor t)
+//!             option(graph g) option(sparse_tensor) option(type tp) list(f32 floats)
+//!             list(i64 ints) list(bytes strings) list(tensor) list(graph) list(sparse_tensor)
+//!             list(type type_protos)
+//! tensor    = i32(data_type) list(i64 dims) list(f32 float_data) list(i32 int32_data)
+//!             list(bytes string_data) list(i64 int64_data) bytes(raw_data)
+//!             list(f64 double_data) list(u64 uint64_data) i32(data_location)
+//!             list(str key str value external_data)
+//! sparse_tensor = option(tensor values) option(tensor indices) list(i64 dims)
+//! graph     = list(node_body) list(str name tensor initializer)
+//!             list(sparse_tensor sparse_initializer) list(str input) list(str output)
+//! type      = str(denotation) (u8(0) | u8(1) i32(elem_type) option(list(dim))
+//!             | u8(2) option(type elem_type) | u8(3) i32(key_type) option(type value_type)
+//!             | u8(4) option(type elem_type) | u8(5) i32(elem_type) option(list(dim)))
+//! option(x) = u8(0) | u8(1) x
+//!
+//! interface  = bytes(GRAPH_INTERFACE_TAG) u32(GRAPH_INTERFACE_VERSION)
+//!              list(value_info input) list(value_info output) list(value_info value_info)
+//! value_info = str(name) (u8(0) | u8(1) i32(elem_type) option(list(dim)) | u8(2))
+//! dim        = u8(0) | u8(1) i64(dim_value) | u8(2) str(dim_param)
+//!
+//! state = bytes(STATE_TAG) u32(STATE_VERSION) list(str name hash)
+//! ```
+//!
+//! Attributes are sorted by name. Operator names and doc strings are not part of the encoding.
+//! Tensors stored outside of the model file are only encoded by their external data entries
+//! (location, offset, length), which do not bind their content: such models are rejected when
+//! they are loaded (see `onnx::load_onnx_model`).
+//! Value infos of non-tensor types are encoded by their kind only. State entries are sorted by
+//! tensor name and tensor hashes are encoded as raw 32 bytes.
+
+use candle_onnx::onnx::{
+    AttributeProto, GraphProto, NodeProto, SparseTensorProto, TensorProto, TensorShapeProto,
+    TypeProto, ValueInfoProto, tensor_shape_proto::dimension, type_proto,
+};
+use std::collections::HashMap;
+
+/// Domain-separation tag of the operator encoding.
+pub const NODE_TAG: &[u8] = b"zkopml/onnx-node";
+
+/// Version of the operator encoding.
+pub const NODE_VERSION: u32 = 2;
+
+/// Returns the canonical encoding of an ONNX operator.
+pub fn encode_node(node: &NodeProto) -> Vec<u8> {
+    let mut encoder = Encoder::default();
+    encoder.bytes(NODE_TAG);
+    encoder.u32(NODE_VERSION);
+    encoder.node(node);
+    encoder.buffer
+}
+
+/// Returns the canonical encoding of the attributes of an ONNX operator, as part of
+/// [`encode_node`].
+pub fn encode_attributes(node: &NodeProto) -> Vec<u8> {
+    let mut encoder = Encoder::default();
+    encoder.attributes(node);
+    encoder.buffer
+}
+
+/// Domain-separation tag of the graph interface encoding.
+pub const GRAPH_INTERFACE_TAG: &[u8] = b"zkopml/onnx-graph-interface";
+
+/// Version of the graph interface encoding.
+pub const GRAPH_INTERFACE_VERSION: u32 = 1;
+
+/// Returns the canonical encoding of the graph interface (declared inputs, outputs and value infos).
+pub fn encode_graph_interface(
+    inputs: &[ValueInfoProto],
+    outputs: &[ValueInfoProto],
+    value_info: &[ValueInfoProto],
+) -> Vec<u8> {
+    let mut encoder = Encoder::default();
+    encoder.bytes(GRAPH_INTERFACE_TAG);
+    encoder.u32(GRAPH_INTERFACE_VERSION);
+    encoder.list(inputs, |e, v| e.value_info(v));
+    encoder.list(outputs, |e, v| e.value_info(v));
+    encoder.list(value_info, |e, v| e.value_info(v));
+    encoder.buffer
+}
+
+/// Domain-separation tag of the state encoding.
+pub const STATE_TAG: &[u8] = b"zkopml/state";
+
+/// Version of the state encoding.
+pub const STATE_VERSION: u32 = 1;
+
+/// Returns the canonical encoding of an inference state, given the hashes of its tensors.
+pub fn encode_state(hashes: &HashMap<String, [u8; 32]>) -> Vec<u8> {
+    let mut entries: Vec<(&String, &[u8; 32])> = hashes.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+
+    let mut encoder = Encoder::default();
+    encoder.bytes(STATE_TAG);
+    encoder.u32(STATE_VERSION);
+    encoder.list(&entries, |e, (name, hash)| {
+        e.str(name);
+        e.buffer.extend_from_slice(*hash);
+    });
+    encoder.buffer
+}
+
+#[derive(Default)]
+pub(crate) struct Encoder {
+    pub(crate) buffer: Vec<u8>,
+}
+
+impl Encoder {
+    pub(crate) fn u8(&mut self, value: u8) {
+        self.buffer.push(value);
+    }
+
+    pub(crate) fn u32(&mut self, value: u32) {
+        self.buffer.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub(crate) fn i32(&mut self, value: i32) {
+        self.buffer.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub(crate) fn i64(&mut self, value: i64) {
+        self.buffer.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub(crate) fn u64(&mut self, value: u64) {
+        self.buffer.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub(crate) fn f32(&mut self, value: f32) {
+        self.u32(value.to_bits());
+    }
+
+    pub(crate) fn f64(&mut self, value: f64) {
+        self.u64(value.to_bits());
+    }
+
+    pub(crate) fn len(&mut self, len: usize) {
+        self.u32(u32::try_from(len).expect("length does not fit into u32"));
+    }
+
+    pub(crate) fn bytes(&mut self, value: &[u8]) {
+        self.len(value.len());
+        self.buffer.extend_from_slice(value);
+    }
+
+    pub(crate) fn str(&mut self, value: &str) {
+        self.bytes(value.as_bytes());
+    }
+
+    pub(crate) fn list<T>(&mut self, values: &[T], mut f: impl FnMut(&mut Self, &T)) {
+        self.len(values.len());
+        for value in values {
+            f(self, value);
+        }
+    }
+
+    fn option<T>(&mut self, value: Option<&T>, f: impl FnOnce(&mut Self, &T)) {
+        match value {
+            Some(value) => {
+                self.u8(1);
+                f(self, value);
+            }
+            None => self.u8(0),
+        }
+    }
+
+    fn node(&mut self, node: &NodeProto) {
+        self.str(&node.op_type);
+        self.str(&node.domain);
+        self.list(&node.input, |e, v| e.str(v));
+        self.list(&node.output, |e, v| e.str(v));
+        self.attributes(node);
+    }
+
+    fn attributes(&mut self, node: &NodeProto) {
+        let mut attributes: Vec<&AttributeProto> = node.attribute.iter().collect();
+        attributes.sort_by(|a, b| a.name.cmp(&b.name));
+        self.list(&attributes, |e, v| e.attribute(v));
+    }
+
+    fn attribute(&mut self, attribute: &AttributeProto) {
+        self.str(&attribute.name);
+        self.str(&attribute.ref_attr_name);
+        self.i32(attribute.r#type);
+        self.f32(attribute.f);
+        self.i64(attribute.i);
+        self.bytes(&attribute.s);
+        self.option(attribute.t.as_ref(), |e, v| e.tensor(v));
+        self.option(attribute.g.as_ref(), |e, v| e.graph(v));
+        self.option(attribute.sparse_tensor.as_ref(), |e, v| e.sparse_tensor(v));
+        self.option(attribute.tp.as_ref(), |e, v| e.type_proto(v));
+        self.list(&attribute.floats, |e, v| e.f32(*v));
+        self.list(&attribute.ints, |e, v| e.i64(*v));
+        self.list(&attribute.strings, |e, v| e.bytes(v));
+        self.list(&attribute.tensors, |e, v| e.tensor(v));
+        self.list(&attribute.graphs, |e, v| e.graph(v));
+        self.list(&attribute.sparse_tensors, |e, v| e.sparse_tensor(v));
+        self.list(&attribute.type_protos, |e, v| e.type_proto(v));
+    }
+
+    fn tensor(&mut self, tensor: &TensorProto) {
+        self.i32(tensor.data_type);
+        self.list(&tensor.dims, |e, v| e.i64(*v));
+        self.list(&tensor.float_data, |e, v| e.f32(*v));
+        self.list(&tensor.int32_data, |e, v| e.i32(*v));
+        self.list(&tensor.string_data, |e, v| e.bytes(v));
+        self.list(&tensor.int64_data, |e, v| e.i64(*v));
+        self.bytes(&tensor.raw_data);
+        self.list(&tensor.double_data, |e, v| e.f64(*v));
+        self.list(&tensor.uint64_data, |e, v| e.u64(*v));
+        self.i32(tensor.data_location);
+        self.list(&tensor.external_data, |e, v| {
+            e.str(&v.key);
+            e.str(&v.value);
+        });
+    }
+
+    fn sparse_tensor(&mut self, sparse_tensor: &SparseTensorProto) {
+        self.option(sparse_tensor.values.as_ref(), |e, v| e.tensor(v));
+        self.option(sparse_tensor.indices.as_ref(), |e, v| e.tensor(v));
+        self.list(&sparse_tensor.dims, |e, v| e.i64(*v));
+    }
+
+    fn graph(&mut self, graph: &GraphProto) {
+        self.list(&graph.node, |e, v| e.node(v));
+        self.list(&graph.initializer, |e, v| {
+            e.str(&v.name);
+            e.tensor(v);
+        });
+        self.list(&graph.sparse_initializer, |e, v| e.sparse_tensor(v));
+        self.list(&graph.input, |e, v| e.str(&v.name));
+        self.list(&graph.output, |e, v| e.str(&v.name));
+    }
+
+    fn value_info(&mut self, value_info: &ValueInfoProto) {
+        self.str(&value_info.name);
+        match value_info.r#type.as_ref().and_then(|t| t.value.as_ref()) {
+            None => self.u8(0),
+            Some(type_proto::Value::TensorType(tensor_type)) => {
+                self.u8(1);
+                self.i32(tensor_type.elem_type);
+                self.option(tensor_type.shape.as_ref(), |e, v| e.shape(v));
+            }
+            Some(_) => self.u8(2),
+        }
+    }
+
+    fn type_proto(&mut self, type_proto: &TypeProto) {
+        self.str(&type_proto.denotation);
+        match type_proto.value.as_ref() {
+            None => self.u8(0),
+            Some(type_proto::Value::TensorType(tensor_type)) => {
+                self.u8(1);
+                self.i32(tensor_type.elem_type);
+                self.option(tensor_type.shape.as_ref(), |e, v| e.shape(v));
+            }
+            Some(type_proto::Value::SequenceType(sequence_type)) => {
+                self.u8(2);
+                self.option(sequence_type.elem_type.as_deref(), |e, v| e.type_proto(v));
+            }
+            Some(type_proto::Value::MapType(map_type)) => {
+                self.u8(3);
+                self.i32(map_type.key_type);
+                self.option(map_type.value_type.as_deref(), |e, v| e.type_proto(v));
+            }
+            Some(type_proto::Value::OptionalType(optional_type)) => {
+                self.u8(4);
+                self.option(optional_type.elem_type.as_deref(), |e, v| e.type_proto(v));
+            }
+            Some(type_proto::Value::SparseTensorType(sparse_tensor_type)) => {
+                self.u8(5);
+                self.i32(sparse_tensor_type.elem_type);
+                self.option(sparse_tensor_type.shape.as_ref(), |e, v| e.shape(v));
+            }
+        }
+    }
+
+    fn shape(&mut self, shape: &TensorShapeProto) {
+        self.list(&shape.dim, |e, v| match v.value.as_ref() {
+            None => e.u8(0),
+            Some(dimension::Value::DimValue(value)) => {
+                e.u8(1);
+                e.i64(*value);
+            }
+            Some(dimension::Value::DimParam(param)) => {
+                e.u8(2);
+                e.str(param);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use candle_onnx::onnx::attribute_proto::AttributeType;
+
+    fn attribute(name: &str, i: i64) -> AttributeProto {
+        AttributeProto {
+            name: name.to_string(),
+            r#type: AttributeType::Int as i32,
+            i,
+            ..Default::default()
+        }
+    }
+
+    /// `y = Conv(x, w)` with two attributes.
+    fn node() -> NodeProto {
+        NodeProto {
+            name: "conv".to_string(),
+            op_type: "Conv".to_string(),
+            input: vec!["x".to_string(), "w".to_string()],
+            output: vec!["y".to_string()],
+            attribute: vec![attribute("group", 1), attribute("auto_pad", 0)],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn deterministic() {
+        assert_eq!(encode_node(&node()), encode_node(&node()));
+        assert!(
+            encode_node(&node())
+                .starts_with(&[&(NODE_TAG.len() as u32).to_le_bytes(), NODE_TAG].concat())
+        );
+    }
+
+    #[test]
+    fn attribute_order() {
+        let mut reordered = node();
+        reordered.attribute.reverse();
+
+        assert_eq!(encode_node(&node()), encode_node(&reordered));
+        assert_eq!(encode_attributes(&node()), encode_attributes(&reordered));
+    }
+
+    #[test]
+    fn operator_name_and_doc_string() {
+        let mut renamed = node();
+        renamed.name = "conv2".to_string();
+        renamed.doc_string = "Convolution".to_string();
+
+        assert_eq!(encode_node(&node()), encode_node(&renamed));
+    }
+
+    #[test]
+    fn distinct_nodes() {
+        let mut op_type = node();
+        op_type.op_type = "ConvTranspose".to_string();
+        let mut input = node();
+        input.input.swap(0, 1);
+        let mut output = node();
+        output.output[0] = "z".to_string();
+        let mut attribute_value = node();
+        attribute_value.attribute[0].i = 2;
+        let mut attribute_type = node();
+        attribute_type.attribute[0].r#type = AttributeType::Float as i32;
+        let mut domain = node();
+        domain.domain = "com.microsoft".to_string();
+
+        let encodings: Vec<Vec<u8>> = [
+            node(),
+            op_type,
+            input,
+            output,
+            attribute_value,
+            attribute_type,
+            domain,
+        ]
+        .iter()
+        .map(encode_node)
+        .collect();
+        for (i, a) in encodings.iter().enumerate() {
+            for b in &encodings[i + 1..] {
+                assert_ne!(a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn state_order() {
+        let entries = [("a", [1; 32]), ("b", [2; 32]), ("c", [3; 32])];
+        let state: HashMap<String, [u8; 32]> = entries
+            .iter()
+            .map(|(name, hash)| (name.to_string(), *hash))
+            .collect();
+        let reversed: HashMap<String, [u8; 32]> = entries
+            .iter()
+            .rev()
+            .map(|(name, hash)| (name.to_string(), *hash))
+            .collect();
+
+        assert_eq!(encode_state(&state), encode_state(&reversed));
+        let mut other = state.clone();
+        other.insert("a".to_string(), [4; 32]);
+        assert_ne!(encode_state(&state), encode_state(&other));
+    }
+}
@@ -0,0 +1,60 @@
+use crate::{data::state_hash, encoding::encode_graph_interface, utils::hash_buffer};
+use candle_onnx::onnx::{GraphProto, ValueInfoProto};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Declared interface of an ONNX graph (inputs, outputs and value infos).
+///
+/// The interface is committed in the last (header) leaf of the model Merkle tree, after the
+/// leaves of all operators.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphInterface {
+    /// Declared graph inputs, without the graph initializers.
+    pub inputs: Vec<ValueInfoProto>,
+    /// Declared graph outputs.
+    pub outputs: Vec<ValueInfoProto>,
+    /// Value infos of the intermediate tensors.
+    pub value_info: Vec<ValueInfoProto>,
+}
+
+impl GraphInterface {
+    pub fn new(graph: &GraphProto) -> Self {
+        let inputs = graph
+            .input
+            .iter()
+            .filter(|input| !graph.initializer.iter().any(|t| t.name == input.name))
+            .cloned()
+            .collect();
+
+        Self {
+            inputs,
+            outputs: graph.output.clone(),
+            value_info: graph.value_info.clone(),
+        }
+    }
+
+    /// Hash of the header leaf.
+    pub fn leaf_hash(&self) -> [u8; 32] {
+        hash_buffer(&encode_graph_interface(
+            &self.inputs,
+            &self.outputs,
+            &self.value_info,
+        ))
+    }
+
+    pub fn output_names(&self) -> Vec<String> {
+        self.outputs.iter().map(|o| o.name.clone()).collect()
+    }
+
+    /// Hash of the final state, restricted to exactly the declared outputs.
+    ///
+    /// Returns `None` if any of the declared outputs is missing from the state.
+    pub fn outputs_hash(&self, hashes: &HashMap<String, [u8; 32]>) -> Option<[u8; 32]> {
+        let mut outputs = HashMap::new();
+        for output in self.outputs.iter() {
+            outputs.insert(output.name.clone(), *hashes.get(&output.name)?);
+        }
+
+        Some(state_hash(&outputs))
+    }
+}
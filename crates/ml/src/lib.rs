@@ -1,4 +1,11 @@
 pub mod data;
+#[cfg(feature = "host")]
+pub mod diff;
+pub mod encoding;
+pub mod interface;
 pub mod merkle;
+#[cfg(feature = "host")]
 pub mod onnx;
+pub mod public_values;
 pub mod utils;
+pub mod witness;
@@ -1,3 +1,4 @@
+use crate::interface::GraphInterface;
 use crate::utils::node_hash;
 use candle_onnx::onnx::{GraphProto, NodeProto};
 use rs_merkle::algorithms::Sha256;
@@ -5,13 +6,18 @@ use rs_merkle::{MerkleProof, MerkleTree};
 
 pub type MerkleTreeHash = [u8; 32];
 
+/// Merkle tree of a model.
+///
+/// The leaves are the hashes of the ONNX operators (in execution order), followed by a single
+/// header leaf committing to the graph interface (see [`GraphInterface`]).
 pub struct ModelMerkleTree {
     pub inner: MerkleTree<Sha256>,
 }
 
 impl ModelMerkleTree {
     pub fn new(nodes: Vec<NodeProto>, graph: GraphProto) -> Self {
-        let leaves: Vec<[u8; 32]> = nodes.iter().map(|node| node_hash(node, &graph)).collect();
+        let mut leaves: Vec<[u8; 32]> = nodes.iter().map(|node| node_hash(node, &graph)).collect();
+        leaves.push(GraphInterface::new(&graph).leaf_hash());
 
         Self {
             inner: MerkleTree::<Sha256>::from_leaves(&leaves),
@@ -41,6 +47,16 @@ impl ModelMerkleTree {
         self.inner.leaves().unwrap().len()
     }
 
+    /// Index of the header leaf (graph interface).
+    pub fn header_index(&self) -> usize {
+        self.total_leaves() - 1
+    }
+
+    /// Number of operator leaves.
+    pub fn num_operators(&self) -> usize {
+        self.total_leaves() - 1
+    }
+
     pub fn prove(&self, indices: Vec<usize>) -> MerkleProof<Sha256> {
         self.inner.proof(&indices)
     }
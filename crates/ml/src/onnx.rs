@@ -1,7 +1,7 @@
 use candle_core::{Device, Tensor};
 use candle_onnx::{
     eval::simple_eval_one,
-    onnx::{GraphProto, ModelProto, NodeProto},
+    onnx::{GraphProto, ModelProto, NodeProto, TensorProto, tensor_proto::DataLocation},
     read_file, simple_eval,
 };
 use std::collections::HashMap;
@@ -110,7 +110,60 @@ impl Model {
     }
 }
 
+/// Loads an ONNX model, rejecting models whose weights are not fully contained in the model file
+/// (externally stored tensors, sparse initializers), as they would not be bound by the Merkle
+/// leaves of the model.
 pub fn load_onnx_model(path: &String) -> anyhow::Result<Model> {
     let model = read_file(&path)?;
+    let graph = model
+        .graph
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("The model {} has no graph", path))?;
+    anyhow::ensure!(
+        graph.sparse_initializer.is_empty(),
+        "Sparse initializers are not supported"
+    );
+    check_embedded_tensors(graph)?;
+
     Ok(Model { inner: model })
 }
+
+/// Checks that the tensors of a graph (initializers and attributes, including the ones of
+/// subgraphs) are stored in the model file.
+fn check_embedded_tensors(graph: &GraphProto) -> anyhow::Result<()> {
+    for tensor in graph.initializer.iter() {
+        check_embedded_tensor(tensor)?;
+    }
+    for node in graph.node.iter() {
+        for attribute in node.attribute.iter() {
+            let sparse_tensors = attribute
+                .sparse_tensor
+                .iter()
+                .chain(attribute.sparse_tensors.iter())
+                .flat_map(|sparse| sparse.values.iter().chain(sparse.indices.iter()));
+            for tensor in attribute
+                .t
+                .iter()
+                .chain(attribute.tensors.iter())
+                .chain(sparse_tensors)
+            {
+                check_embedded_tensor(tensor)?;
+            }
+            for subgraph in attribute.g.iter().chain(attribute.graphs.iter()) {
+                check_embedded_tensors(subgraph)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn check_embedded_tensor(tensor: &TensorProto) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        tensor.data_location != DataLocation::External as i32 && tensor.external_data.is_empty(),
+        "Tensor {} is stored outside of the model file, which is not supported",
+        tensor.name
+    );
+
+    Ok(())
+}
@@ -0,0 +1,302 @@
+use alloy_primitives::{Address, B256, U256};
+use alloy_sol_types::{SolValue, sol};
+use serde::{Deserialize, Serialize};
+
+sol! {
+    /// Context a proof is bound to, so it cannot be replayed for another challenge or another
+    /// full-ZK inference.
+    ///
+    /// Mirrors the `ProofContext` struct of the `IModelRegistry` interface.
+    #[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+    struct ProofContext {
+        /// Id of the chain the verifying contract is deployed on.
+        uint256 chainId;
+        /// Address of the verifying contract (FaultProof for challenges, ModelRegistry for
+        /// full-ZK inferences).
+        address verifier;
+        /// Domain of the proof, `PROOF_DOMAIN_CHALLENGE` or `PROOF_DOMAIN_INFERENCE`.
+        uint8 domain;
+        /// Id of the challenge or of the inference, depending on the domain.
+        uint256 id;
+    }
+
+    /// Public values committed by the SP1 program (ABI-encoded).
+    ///
+    /// Mirrors the `PublicValues` struct of the `FaultProof` contract.
+    #[derive(Debug, PartialEq, Eq)]
+    struct PublicValues {
+        /// Challenge the proof is bound to.
+        ProofContext context;
+        /// Merkle root of the model operators.
+        bytes32 merkleRoot;
+        /// Number of ONNX operators of the model (the Merkle tree has one more header leaf).
+        uint256 numOperators;
+        /// Index of the executed ONNX operator (Merkle leaf index).
+        uint256 operatorIndex;
+        /// Input data hash of the operator execution.
+        bytes32 inputDataHash;
+        /// Output data hash of the operator execution.
+        bytes32 outputDataHash;
+    }
+
+    /// Public values committed by the aggregation SP1 program (ABI-encoded).
+    ///
+    /// Attests to a chain of operator executions from the input to the output state hash.
+    ///
+    /// Mirrors the `AggregatePublicValues` struct of the `IModelRegistry` interface.
+    #[derive(Debug, PartialEq, Eq)]
+    struct AggregatePublicValues {
+        /// Challenge the aggregated proofs are bound to.
+        ProofContext context;
+        /// Verification key digest of the operator SP1 program.
+        bytes32 operatorVkey;
+        /// Merkle root of the model operators.
+        bytes32 merkleRoot;
+        /// Number of ONNX operators of the model.
+        uint256 numOperators;
+        /// Index of the first executed ONNX operator.
+        uint256 firstOperator;
+        /// Index of the last executed ONNX operator.
+        uint256 lastOperator;
+        /// Input data hash of the first operator execution.
+        bytes32 inputDataHash;
+        /// Output data hash of the last operator execution.
+        bytes32 outputDataHash;
+    }
+}
+
+/// Domain of the proofs resolving a challenge of the FaultProof contract.
+pub const PROOF_DOMAIN_CHALLENGE: u8 = 1;
+
+/// Domain of the proofs responding to a full-ZK inference of the ModelRegistry contract.
+pub const PROOF_DOMAIN_INFERENCE: u8 = 2;
+
+impl ProofContext {
+    /// Context of a proof resolving a challenge.
+    pub fn challenge(chain_id: u64, fault_proof: Address, challenge_id: U256) -> Self {
+        Self {
+            chainId: U256::from(chain_id),
+            verifier: fault_proof,
+            domain: PROOF_DOMAIN_CHALLENGE,
+            id: challenge_id,
+        }
+    }
+
+    /// Context of a proof responding to a full-ZK inference.
+    pub fn inference(chain_id: u64, model_registry: Address, inference_id: U256) -> Self {
+        Self {
+            chainId: U256::from(chain_id),
+            verifier: model_registry,
+            domain: PROOF_DOMAIN_INFERENCE,
+            id: inference_id,
+        }
+    }
+}
+
+impl PublicValues {
+    pub fn new(
+        context: ProofContext,
+        merkle_root: [u8; 32],
+        num_operators: usize,
+        operator_index: usize,
+        input_data_hash: [u8; 32],
+        output_data_hash: [u8; 32],
+    ) -> Self {
+        Self {
+            context,
+            merkleRoot: B256::from(merkle_root),
+            numOperators: U256::from(num_operators),
+            operatorIndex: U256::from(operator_index),
+            inputDataHash: B256::from(input_data_hash),
+            outputDataHash: B256::from(output_data_hash),
+        }
+    }
+
+    /// Returns the ABI encoding, as committed by the SP1 program.
+    pub fn encode(&self) -> Vec<u8> {
+        self.abi_encode()
+    }
+
+    /// Decodes the ABI-encoded public values of a proof.
+    #[cfg(feature = "host")]
+    pub fn decode(bytes: &[u8]) -> anyhow::Result<Self> {
+        Self::abi_decode(bytes).map_err(|e| anyhow::anyhow!("Invalid public values: {}", e))
+    }
+
+    /// Checks the public values against the expected operator execution.
+    #[cfg(feature = "host")]
+    pub fn verify(
+        &self,
+        context: &ProofContext,
+        merkle_root: [u8; 32],
+        num_operators: usize,
+        operator_index: usize,
+        input_data_hash: [u8; 32],
+        output_data_hash: [u8; 32],
+    ) -> anyhow::Result<()> {
+        anyhow::ensure!(&self.context == context, "proof context does not match");
+        anyhow::ensure!(
+            self.merkleRoot == B256::from(merkle_root),
+            "merkle root does not match"
+        );
+        anyhow::ensure!(
+            self.numOperators == U256::from(num_operators),
+            "number of operators does not match"
+        );
+        anyhow::ensure!(
+            self.operatorIndex == U256::from(operator_index),
+            "operator index does not match"
+        );
+        anyhow::ensure!(
+            self.inputDataHash == B256::from(input_data_hash),
+            "input data hash does not match"
+        );
+        anyhow::ensure!(
+            self.outputDataHash == B256::from(output_data_hash),
+            "output data hash does not match"
+        );
+
+        Ok(())
+    }
+}
+
+/// Verification key digest of an SP1 program as committed by the aggregation program (the
+/// words of `SP1VerifyingKey::hash_u32`, big-endian).
+pub fn vkey_digest(vkey: &[u32; 8]) -> [u8; 32] {
+    let mut digest = [0u8; 32];
+    for (i, word) in vkey.iter().enumerate() {
+        digest[i * 4..(i + 1) * 4].copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
+
+impl AggregatePublicValues {
+    /// Returns the ABI encoding, as committed by the aggregation SP1 program.
+    pub fn encode(&self) -> Vec<u8> {
+        self.abi_encode()
+    }
+
+    /// Decodes the ABI-encoded public values of an aggregated proof.
+    #[cfg(feature = "host")]
+    pub fn decode(bytes: &[u8]) -> anyhow::Result<Self> {
+        Self::abi_decode(bytes)
+            .map_err(|e| anyhow::anyhow!("Invalid aggregate public values: {}", e))
+    }
+}
+
+#[cfg(all(test, feature = "host"))]
+mod tests {
+    use super::*;
+
+    fn context() -> ProofContext {
+        ProofContext::challenge(31337, Address::repeat_byte(0xaa), U256::from(7))
+    }
+
+    /// Splits an ABI encoding of static values into its 32-byte words.
+    fn words(bytes: &[u8]) -> Vec<&[u8]> {
+        assert_eq!(bytes.len() % 32, 0);
+        bytes.chunks(32).collect()
+    }
+
+    fn uint(value: u64) -> [u8; 32] {
+        U256::from(value).to_be_bytes()
+    }
+
+    fn context_words() -> Vec<[u8; 32]> {
+        let mut verifier = [0u8; 32];
+        verifier[12..].copy_from_slice(&[0xaa; 20]);
+        vec![
+            uint(31337),
+            verifier,
+            uint(PROOF_DOMAIN_CHALLENGE as u64),
+            uint(7),
+        ]
+    }
+
+    #[test]
+    fn public_values_round_trip() {
+        let values = PublicValues::new(context(), [1; 32], 10, 3, [2; 32], [3; 32]);
+
+        let decoded = PublicValues::decode(&values.encode()).unwrap();
+        assert_eq!(decoded, values);
+        decoded
+            .verify(&context(), [1; 32], 10, 3, [2; 32], [3; 32])
+            .unwrap();
+        assert!(
+            decoded
+                .verify(&context(), [1; 32], 10, 4, [2; 32], [3; 32])
+                .is_err()
+        );
+    }
+
+    /// The word order must match the `PublicValues` struct of `FaultProof.sol`.
+    #[test]
+    fn public_values_layout() {
+        let values = PublicValues::new(context(), [1; 32], 10, 3, [2; 32], [3; 32]);
+
+        let mut expected = context_words();
+        expected.extend([[1; 32], uint(10), uint(3), [2; 32], [3; 32]]);
+        let encoded = values.encode();
+        assert_eq!(
+            words(&encoded),
+            expected.iter().map(|w| &w[..]).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn aggregate_public_values_round_trip() {
+        let values = AggregatePublicValues {
+            context: ProofContext::inference(31337, Address::repeat_byte(0xaa), U256::from(7)),
+            operatorVkey: B256::repeat_byte(4),
+            merkleRoot: B256::repeat_byte(1),
+            numOperators: U256::from(10),
+            firstOperator: U256::from(0),
+            lastOperator: U256::from(9),
+            inputDataHash: B256::repeat_byte(2),
+            outputDataHash: B256::repeat_byte(3),
+        };
+
+        assert_eq!(
+            AggregatePublicValues::decode(&values.encode()).unwrap(),
+            values
+        );
+    }
+
+    /// The word order must match the `AggregatePublicValues` struct of `IModelRegistry.sol`.
+    #[test]
+    fn aggregate_public_values_layout() {
+        let values = AggregatePublicValues {
+            context: context(),
+            operatorVkey: B256::repeat_byte(4),
+            merkleRoot: B256::repeat_byte(1),
+            numOperators: U256::from(10),
+            firstOperator: U256::from(2),
+            lastOperator: U256::from(5),
+            inputDataHash: B256::repeat_byte(2),
+            outputDataHash: B256::repeat_byte(3),
+        };
+
+        let mut expected = context_words();
+        expected.extend([
+            [4; 32],
+            [1; 32],
+            uint(10),
+            uint(2),
+            uint(5),
+            [2; 32],
+            [3; 32],
+        ]);
+        let encoded = values.encode();
+        assert_eq!(
+            words(&encoded),
+            expected.iter().map(|w| &w[..]).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn vkey_digest_words() {
+        let digest = vkey_digest(&[1, 2, 3, 4, 5, 6, 7, 0x0102_0304]);
+        assert_eq!(&digest[..4], &[0, 0, 0, 1]);
+        assert_eq!(&digest[28..], &[1, 2, 3, 4]);
+    }
+}
@@ -4,7 +4,7 @@ use candle_onnx::{
 };
 use sha2::{Digest, Sha256};
 
-use crate::data::tensor_hash;
+use crate::{data::tensor_hash, encoding::encode_node};
 
 pub fn hash_buffer(buffer: &[u8]) -> [u8; 32] {
     let mut hasher = Sha256::new();
@@ -12,22 +12,43 @@ pub fn hash_buffer(buffer: &[u8]) -> [u8; 32] {
     hasher.finalize().into()
 }
 
-pub fn node_hash(node: &NodeProto, graph: &GraphProto) -> [u8; 32] {
-    let mut buffer = Vec::new();
-    buffer.extend_from_slice(&serde_json::to_vec(&node).unwrap());
+/// Inputs of the leaf hash of an ONNX operator.
+pub struct NodeHashInputs {
+    /// Canonical encoding of the operator (op type, attributes and wiring).
+    pub node: Vec<u8>,
+    /// Hashes of the graph initializers used by the operator, sorted by name.
+    pub initializers: Vec<(String, [u8; 32])>,
+}
 
+pub fn node_hash_inputs(node: &NodeProto, graph: &GraphProto) -> NodeHashInputs {
     let mut node_inputs = node.input.clone();
     node_inputs.sort();
 
+    let mut initializers = Vec::new();
     for input in node_inputs.iter() {
         for t in graph.initializer.iter() {
             if input == &t.name {
                 let tensor = get_tensor(t, t.name.as_str()).unwrap();
-                buffer.extend_from_slice(&tensor_hash(&tensor));
+                initializers.push((t.name.clone(), tensor_hash(&tensor)));
                 break;
             }
         }
     }
 
+    NodeHashInputs {
+        node: encode_node(node),
+        initializers,
+    }
+}
+
+pub fn node_hash(node: &NodeProto, graph: &GraphProto) -> [u8; 32] {
+    let inputs = node_hash_inputs(node, graph);
+
+    let mut buffer = Vec::new();
+    buffer.extend_from_slice(&inputs.node);
+    for (_, hash) in inputs.initializers.iter() {
+        buffer.extend_from_slice(hash);
+    }
+
     hash_buffer(&buffer)
 }
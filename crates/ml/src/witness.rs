@@ -0,0 +1,356 @@
+#[cfg(any(feature = "host", feature = "guest"))]
+use crate::data::tensor_hash;
+#[cfg(feature = "guest")]
+use crate::public_values::{AggregatePublicValues, PublicValues, vkey_digest};
+#[cfg(feature = "guest")]
+use crate::{data::state_hash, encoding::encode_node, utils::hash_buffer};
+use crate::{interface::GraphInterface, merkle::MerkleTreeHash, public_values::ProofContext};
+#[cfg(feature = "host")]
+use crate::{merkle::ModelMerkleTree, onnx::Model};
+#[cfg(feature = "guest")]
+use alloy_primitives::{B256, U256};
+#[cfg(feature = "guest")]
+use alloy_sol_types::SolValue;
+use candle_core::Tensor;
+use candle_onnx::onnx::NodeProto;
+#[cfg(feature = "guest")]
+use rs_merkle::{MerkleProof, algorithms::Sha256};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Witness of a single ONNX operator execution, the input of the SP1 program.
+///
+/// Built on the host with `OperatorWitnessBuilder` (`host` feature) and checked in the guest with
+/// `OperatorWitness::verify` (`guest` feature).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OperatorWitness {
+    /// Merkle root of the model.
+    pub merkle_root: MerkleTreeHash,
+    /// Indices of the executed operators (Merkle leaf indices).
+    pub leaf_indices: Vec<usize>,
+    /// Leaf hashes of the executed operators.
+    pub leaf_hashes: Vec<MerkleTreeHash>,
+    /// Total number of leaves of the Merkle tree (operators and the header leaf).
+    pub total_leaves: usize,
+    /// Merkle proof of the executed operators (and of the header leaf for the last operator).
+    pub merkle_proof: Vec<u8>,
+    /// Operator inputs computed during the inference.
+    pub inputs: HashMap<String, Tensor>,
+    /// Operator inputs that are graph initializers (committed in the operator leaf).
+    pub initializers: HashMap<String, Tensor>,
+    /// Hashes of all tensors of the state before the operator execution.
+    pub state_hashes: HashMap<String, [u8; 32]>,
+    /// Executed ONNX operator.
+    pub node: NodeProto,
+    /// Graph interface, only for the last operator.
+    pub graph_interface: Option<GraphInterface>,
+    /// Challenge the proof is bound to (committed as is in the public values).
+    pub context: ProofContext,
+}
+
+impl OperatorWitness {
+    #[cfg(feature = "host")]
+    pub fn builder<'a>(
+        model: &'a Model,
+        merkle_tree: &'a ModelMerkleTree,
+    ) -> OperatorWitnessBuilder<'a> {
+        OperatorWitnessBuilder::new(model, merkle_tree)
+    }
+
+    /// Checks the witness against the model commitment (guest side).
+    ///
+    /// Panics if the witness is invalid, so no proof can be generated for it.
+    #[cfg(feature = "guest")]
+    pub fn verify(self) -> VerifiedOperator {
+        // TODO: execute multiple operators
+        assert!(
+            self.leaf_indices.len() == 1,
+            "Expected exactly one operator"
+        );
+        let operator_index = self.leaf_indices[0];
+        // the number of operators is committed, so the shape of the tree cannot be forged
+        let num_operators = self.total_leaves - 1;
+        assert!(
+            operator_index < num_operators,
+            "Operator index out of range"
+        );
+
+        // verify merkle proof (the last operator also proves the header leaf with the graph interface)
+        let last_operator = operator_index == num_operators - 1;
+        let mut proof_indices = self.leaf_indices.clone();
+        let mut proof_hashes = self.leaf_hashes.clone();
+        if let Some(graph_interface) = self.graph_interface.as_ref() {
+            proof_indices.push(self.total_leaves - 1);
+            proof_hashes.push(graph_interface.leaf_hash());
+        }
+        assert!(
+            !last_operator || self.graph_interface.is_some(),
+            "Missing graph interface for the last operator"
+        );
+        let proof =
+            MerkleProof::<Sha256>::try_from(self.merkle_proof).expect("Invalid merkle proof");
+        assert!(
+            proof.verify(
+                self.merkle_root,
+                &proof_indices,
+                &proof_hashes,
+                self.total_leaves
+            ),
+            "Invalid merkle proof"
+        );
+
+        // verify the input tensors against the state
+        let mut inputs = HashMap::new();
+        for (name, tensor) in self.inputs.into_iter().chain(self.initializers.clone()) {
+            let hash = self.state_hashes.get(&name).expect("Missing input hash");
+            assert!(&tensor_hash(&tensor) == hash, "Input hash does not match");
+            inputs.insert(name, tensor);
+        }
+
+        // verify the operator leaf hash (operator encoding and its graph initializers)
+        // TODO: we could precompute all graph initializers beforehand in production (when commiting in the registry to the model) and just verfiy ZK proofs here
+        assert!(
+            self.initializers
+                .keys()
+                .all(|name| self.node.input.contains(name)),
+            "Initializer is not an operator input"
+        );
+        let mut node_inputs = self.node.input.clone();
+        node_inputs.sort();
+        let mut node_buffer = encode_node(&self.node);
+        for node_input in node_inputs.iter() {
+            if self.initializers.contains_key(node_input) {
+                node_buffer.extend_from_slice(self.state_hashes.get(node_input).unwrap());
+            }
+        }
+        assert!(
+            hash_buffer(&node_buffer) == self.leaf_hashes[0],
+            "Operator leaf hash does not match"
+        );
+
+        VerifiedOperator {
+            merkle_root: self.merkle_root,
+            num_operators,
+            operator_index,
+            last_operator,
+            inputs,
+            state_hashes: self.state_hashes,
+            node: self.node,
+            graph_interface: self.graph_interface,
+            context: self.context,
+        }
+    }
+}
+
+/// Operator execution checked against the model commitment, ready to be executed.
+#[cfg(feature = "guest")]
+pub struct VerifiedOperator {
+    pub merkle_root: MerkleTreeHash,
+    /// Number of operators of the model (committed in the public values).
+    pub num_operators: usize,
+    pub operator_index: usize,
+    /// Whether this is the last operator of the model.
+    pub last_operator: bool,
+    /// Operator inputs (including graph initializers).
+    pub inputs: HashMap<String, Tensor>,
+    pub state_hashes: HashMap<String, [u8; 32]>,
+    pub node: NodeProto,
+    pub graph_interface: Option<GraphInterface>,
+    pub context: ProofContext,
+}
+
+#[cfg(feature = "guest")]
+impl VerifiedOperator {
+    /// Hash of the state before the operator execution.
+    pub fn input_data_hash(&self) -> [u8; 32] {
+        state_hash(&self.state_hashes)
+    }
+
+    /// Hash of the state after the operator execution, given the tensors after the execution.
+    ///
+    /// The final state is restricted to exactly the declared graph outputs.
+    pub fn output_data_hash(&self, tensors: &HashMap<String, Tensor>) -> [u8; 32] {
+        let mut state_hashes = self.state_hashes.clone();
+        for (name, tensor) in tensors.iter() {
+            if !state_hashes.contains_key(name) {
+                state_hashes.insert(name.clone(), tensor_hash(tensor));
+            }
+        }
+
+        match self.graph_interface.as_ref() {
+            Some(graph_interface) if self.last_operator => graph_interface
+                .outputs_hash(&state_hashes)
+                .expect("Missing declared graph output"),
+            _ => state_hash(&state_hashes),
+        }
+    }
+}
+
+/// Builds the [`OperatorWitness`] of an operator from the model, its Merkle tree and the state
+/// of the inference before the operator.
+#[cfg(feature = "host")]
+pub struct OperatorWitnessBuilder<'a> {
+    model: &'a Model,
+    merkle_tree: &'a ModelMerkleTree,
+    operator_index: Option<usize>,
+    state: Option<&'a HashMap<String, Tensor>>,
+    context: ProofContext,
+}
+
+#[cfg(feature = "host")]
+impl<'a> OperatorWitnessBuilder<'a> {
+    pub fn new(model: &'a Model, merkle_tree: &'a ModelMerkleTree) -> Self {
+        Self {
+            model,
+            merkle_tree,
+            operator_index: None,
+            state: None,
+            context: ProofContext::default(),
+        }
+    }
+
+    /// Index of the operator to execute.
+    pub fn operator_index(mut self, operator_index: usize) -> Self {
+        self.operator_index = Some(operator_index);
+        self
+    }
+
+    /// All tensors of the inference state before the operator (inputs, initializers and
+    /// outputs of the previous operators).
+    pub fn state(mut self, state: &'a HashMap<String, Tensor>) -> Self {
+        self.state = Some(state);
+        self
+    }
+
+    /// Challenge the proof is bound to. Defaults to an empty context, which is not accepted by
+    /// the FaultProof contract.
+    pub fn context(mut self, context: ProofContext) -> Self {
+        self.context = context;
+        self
+    }
+
+    pub fn build(self) -> anyhow::Result<OperatorWitness> {
+        let operator_index = self
+            .operator_index
+            .ok_or_else(|| anyhow::anyhow!("Missing operator index"))?;
+        let state = self
+            .state
+            .ok_or_else(|| anyhow::anyhow!("Missing inference state"))?;
+        let graph = self
+            .model
+            .graph()
+            .ok_or_else(|| anyhow::anyhow!("Missing model graph"))?;
+        let node = graph
+            .node
+            .get(operator_index)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Operator index {} out of range", operator_index))?;
+
+        // The last operator also proves the header leaf (graph interface)
+        let leaf_indices = vec![operator_index];
+        let graph_interface = (operator_index == self.merkle_tree.num_operators() - 1)
+            .then(|| GraphInterface::new(&graph));
+        let mut proof_indices = leaf_indices.clone();
+        if graph_interface.is_some() {
+            proof_indices.push(self.merkle_tree.header_index());
+        }
+
+        let mut inputs = HashMap::new();
+        let mut initializers = HashMap::new();
+        for name in node.input.iter() {
+            // Optional inputs are not part of the state
+            let Some(tensor) = state.get(name) else {
+                continue;
+            };
+            if graph.initializer.iter().any(|t| &t.name == name) {
+                initializers.insert(name.clone(), tensor.clone());
+            } else {
+                inputs.insert(name.clone(), tensor.clone());
+            }
+        }
+
+        let state_hashes = state
+            .iter()
+            .map(|(name, tensor)| (name.clone(), tensor_hash(tensor)))
+            .collect();
+
+        Ok(OperatorWitness {
+            merkle_root: self.merkle_tree.root(),
+            leaf_hashes: self.merkle_tree.leaves_hashes(leaf_indices.clone()),
+            leaf_indices,
+            total_leaves: self.merkle_tree.total_leaves(),
+            merkle_proof: self.merkle_tree.prove(proof_indices).to_bytes(),
+            inputs,
+            initializers,
+            state_hashes,
+            node,
+            graph_interface,
+            context: self.context,
+        })
+    }
+}
+
+/// Witness of the aggregation of consecutive operator proofs, the input of the aggregation SP1
+/// program.
+///
+/// The operator proofs themselves are passed to the program as SP1 proofs to verify with
+/// recursion.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AggregationWitness {
+    /// Verification key digest of the operator SP1 program.
+    pub operator_vkey: [u32; 8],
+    /// ABI-encoded public values of the operator proofs, in execution order.
+    pub public_values: Vec<Vec<u8>>,
+}
+
+impl AggregationWitness {
+    /// Checks that the operator executions form a chain of state transitions (guest side).
+    ///
+    /// Panics if the operators are not consecutive executions of the same model and challenge,
+    /// or if the output state of an operator is not the input state of the next one.
+    #[cfg(feature = "guest")]
+    pub fn verify(&self) -> AggregatePublicValues {
+        assert!(
+            !self.public_values.is_empty(),
+            "Expected at least one operator proof"
+        );
+        let values: Vec<PublicValues> = self
+            .public_values
+            .iter()
+            .map(|v| PublicValues::abi_decode(v).expect("Invalid public values"))
+            .collect();
+
+        for pair in values.windows(2) {
+            let (prev, next) = (&pair[0], &pair[1]);
+            assert!(next.context == prev.context, "Proof context does not match");
+            assert!(
+                next.merkleRoot == prev.merkleRoot,
+                "Merkle root does not match"
+            );
+            assert!(
+                next.numOperators == prev.numOperators,
+                "Number of operators does not match"
+            );
+            assert!(
+                next.operatorIndex == prev.operatorIndex + U256::from(1),
+                "Operators are not consecutive"
+            );
+            assert!(
+                next.inputDataHash == prev.outputDataHash,
+                "State hashes do not form a chain"
+            );
+        }
+
+        let (first, last) = (&values[0], &values[values.len() - 1]);
+        AggregatePublicValues {
+            context: first.context.clone(),
+            operatorVkey: B256::from(vkey_digest(&self.operator_vkey)),
+            merkleRoot: first.merkleRoot,
+            numOperators: first.numOperators,
+            firstOperator: first.operatorIndex,
+            lastOperator: last.operatorIndex,
+            inputDataHash: first.inputDataHash,
+            outputDataHash: last.outputDataHash,
+        }
+    }
+}
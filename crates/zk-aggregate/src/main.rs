@@ -0,0 +1,37 @@
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use sha2::{Digest, Sha256};
+use zkopml_ml::witness::AggregationWitness;
+
+pub fn main() {
+    println!("cycle-tracker-start: inputs");
+
+    // read the aggregation witness (the operator proofs are read by the recursion verifier)
+    let witness = sp1_zkvm::io::read::<AggregationWitness>();
+
+    println!("cycle-tracker-end: inputs");
+
+    println!("cycle-tracker-start: verify operator proofs");
+
+    // verify the operator proofs with SP1 recursion
+    for public_values in witness.public_values.iter() {
+        let public_values_digest = Sha256::digest(public_values);
+        sp1_zkvm::lib::verify::verify_sp1_proof(
+            &witness.operator_vkey,
+            &public_values_digest.into(),
+        );
+    }
+
+    println!("cycle-tracker-end: verify operator proofs");
+
+    println!("cycle-tracker-start: verify state transitions");
+
+    // verify the chain of state transitions
+    let public_values = witness.verify();
+
+    println!("cycle-tracker-end: verify state transitions");
+
+    // commit to the aggregate public values (ABI-encoded)
+    sp1_zkvm::io::commit_slice(&public_values.encode());
+}